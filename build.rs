@@ -5,20 +5,41 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let protos_dir = "proto";
 
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("admin_descriptor.bin"))
+        .compile_protos(&["admin.proto"], &[protos_dir])?;
+
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("events_descriptor.bin"))
+        .compile_protos(&["events.proto"], &[protos_dir])?;
+
     tonic_build::configure()
         .file_descriptor_set_path(out_dir.join("guild_descriptor.bin"))
+        .extern_path(".logs", "crate::services::logs_service::proto")
+        .extern_path(".moderation", "crate::services::moderation_service::proto")
+        .extern_path(".staff", "crate::permissions::proto")
+        .extern_path(".tickets", "crate::services::tickets_service::proto")
         .compile_protos(&["guild.proto"], &[protos_dir])?;
 
     tonic_build::configure()
         .file_descriptor_set_path(out_dir.join("logs_descriptor.bin"))
+        .extern_path(".pagination", "crate::pagination::proto")
         .compile_protos(&["logs.proto"], &[protos_dir])?;
 
     tonic_build::configure()
         .file_descriptor_set_path(out_dir.join("moderation_descriptor.bin"))
+        .extern_path(".pagination", "crate::pagination::proto")
+        .extern_path(".staff", "crate::permissions::proto")
         .compile_protos(&["moderation.proto"], &[protos_dir])?;
 
+    tonic_build::configure().compile_protos(&["pagination.proto"], &[protos_dir])?;
+
+    tonic_build::configure().compile_protos(&["staff.proto"], &[protos_dir])?;
+
     tonic_build::configure()
         .file_descriptor_set_path(out_dir.join("tickets_descriptor.bin"))
+        .extern_path(".pagination", "crate::pagination::proto")
+        .extern_path(".staff", "crate::permissions::proto")
         .compile_protos(&["tickets.proto"], &[protos_dir])?;
 
     Ok(())
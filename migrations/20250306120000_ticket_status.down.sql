@@ -0,0 +1,9 @@
+-- Add down migration script here
+DROP TABLE ticket_history;
+
+ALTER TABLE ticket
+DROP COLUMN close_reason,
+DROP COLUMN claimed_by,
+DROP COLUMN status;
+
+DROP TYPE ticket_status;
@@ -0,0 +1,30 @@
+-- Add up migration script here
+CREATE TYPE ticket_status AS ENUM ('open', 'claimed', 'on_hold', 'closed');
+
+ALTER TABLE ticket
+ADD COLUMN status ticket_status NOT NULL DEFAULT 'open',
+ADD COLUMN claimed_by bigint,
+ADD COLUMN close_reason text;
+
+CREATE TABLE ticket_history (
+    id serial PRIMARY KEY,
+    ticket_id int NOT NULL REFERENCES ticket (id) ON DELETE CASCADE,
+    actor_id bigint NOT NULL,
+    from_status ticket_status,
+    to_status ticket_status NOT NULL,
+    reason text,
+    created_at timestamp NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX ticket_history_ticket_id_idx ON ticket_history (ticket_id, created_at);
+
+-- Tickets without an author are attributed to 0, which is never a valid Discord user ID.
+INSERT INTO
+    ticket_history (ticket_id, actor_id, to_status, created_at)
+SELECT
+    id,
+    COALESCE(author_id, 0),
+    'open',
+    created_at
+FROM
+    ticket;
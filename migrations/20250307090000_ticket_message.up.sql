@@ -0,0 +1,11 @@
+-- Add up migration script here
+CREATE TABLE ticket_message (
+    id serial PRIMARY KEY,
+    ticket_id int NOT NULL REFERENCES ticket (id) ON DELETE CASCADE,
+    author_id bigint NOT NULL,
+    content text NOT NULL,
+    attachment_urls text[] NOT NULL DEFAULT '{}',
+    created_at timestamp NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX ticket_message_ticket_id_idx ON ticket_message (ticket_id, created_at, id);
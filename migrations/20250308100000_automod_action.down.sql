@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE automod_infraction;
+
+DROP TYPE automod_action;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+CREATE TYPE automod_action AS ENUM ('kick', 'ban');
+
+CREATE TABLE automod_infraction (
+    id serial PRIMARY KEY,
+    guild_id bigint REFERENCES guild (guild_id) ON DELETE CASCADE,
+    target_user_id bigint NOT NULL,
+    warn_id int REFERENCES warn (id) ON DELETE SET NULL,
+    action automod_action NOT NULL,
+    warn_count int NOT NULL,
+    created_at timestamp NOT NULL DEFAULT NOW ()
+);
@@ -0,0 +1,82 @@
+-- Add down migration script here
+CREATE TABLE warn (
+    id serial PRIMARY KEY,
+    guild_id bigint REFERENCES guild (guild_id) ON DELETE CASCADE,
+    staff_member_id bigint,
+    target_user_id bigint,
+    reason text NOT NULL,
+    created_at timestamp NOT NULL DEFAULT NOW ()
+);
+
+INSERT INTO
+    warn (
+        id,
+        guild_id,
+        staff_member_id,
+        target_user_id,
+        reason,
+        created_at
+    )
+SELECT
+    id,
+    guild_id,
+    staff_member_id,
+    target_user_id,
+    reason,
+    created_at
+FROM
+    infraction
+WHERE
+    infraction_type = 'warn';
+
+SELECT
+    setval(
+        pg_get_serial_sequence('warn', 'id'),
+        COALESCE(MAX(id), 0) + 1,
+        false
+    )
+FROM
+    warn;
+
+CREATE TYPE automod_action AS ENUM ('kick', 'ban');
+
+CREATE TABLE automod_infraction (
+    id serial PRIMARY KEY,
+    guild_id bigint REFERENCES guild (guild_id) ON DELETE CASCADE,
+    target_user_id bigint NOT NULL,
+    warn_id int REFERENCES warn (id) ON DELETE SET NULL,
+    action automod_action NOT NULL,
+    warn_count int NOT NULL,
+    created_at timestamp NOT NULL DEFAULT NOW ()
+);
+
+INSERT INTO
+    automod_infraction (
+        guild_id,
+        target_user_id,
+        warn_id,
+        action,
+        warn_count,
+        created_at
+    )
+SELECT
+    guild_id,
+    target_user_id,
+    triggered_by,
+    infraction_type::text::automod_action,
+    0,
+    created_at
+FROM
+    infraction
+WHERE
+    infraction_type IN ('kick', 'ban')
+    AND staff_member_id IS NULL
+    AND triggered_by IS NOT NULL;
+
+DROP TABLE infraction;
+
+DROP FUNCTION assign_case_number;
+
+DROP TABLE guild_case_counter;
+
+DROP TYPE infraction_type;
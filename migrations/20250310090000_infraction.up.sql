@@ -0,0 +1,159 @@
+-- Add up migration script here
+CREATE TYPE infraction_type AS ENUM ('warn', 'timeout', 'kick', 'ban', 'unban', 'note');
+
+CREATE TABLE infraction (
+    id serial PRIMARY KEY,
+    guild_id bigint NOT NULL REFERENCES guild (guild_id) ON DELETE CASCADE,
+    case_number int,
+    infraction_type infraction_type NOT NULL,
+    staff_member_id bigint,
+    target_user_id bigint NOT NULL,
+    reason text NOT NULL,
+    expires_at timestamp,
+    triggered_by int REFERENCES infraction (id) ON DELETE SET NULL,
+    created_at timestamp NOT NULL DEFAULT NOW (),
+    UNIQUE (guild_id, case_number)
+);
+
+CREATE INDEX infraction_target_idx ON infraction (guild_id, target_user_id, created_at, id);
+
+CREATE TABLE guild_case_counter (
+    guild_id bigint PRIMARY KEY REFERENCES guild (guild_id) ON DELETE CASCADE,
+    last_case_number int NOT NULL
+);
+
+-- Every infraction needs a guild and a target, so rows without them can't be carried over. They
+-- are never dropped silently: an operator has to remove or fix them before migrating.
+DO $$
+DECLARE
+    orphaned_warns bigint;
+    orphaned_actions bigint;
+BEGIN
+    SELECT COUNT(*) INTO orphaned_warns FROM warn
+    WHERE guild_id IS NULL OR target_user_id IS NULL;
+
+    SELECT COUNT(*) INTO orphaned_actions FROM automod_infraction
+    WHERE guild_id IS NULL;
+
+    IF orphaned_warns > 0 OR orphaned_actions > 0 THEN
+        RAISE EXCEPTION '% warns and % automod actions have no guild or target user and cannot become infractions',
+            orphaned_warns, orphaned_actions
+        USING HINT = 'Delete or fix the rows in warn WHERE guild_id IS NULL OR target_user_id IS NULL and in automod_infraction WHERE guild_id IS NULL, then rerun the migration.';
+    END IF;
+END;
+$$;
+
+-- Existing warns keep their IDs so clients holding them can still look them up.
+INSERT INTO
+    infraction (
+        id,
+        guild_id,
+        infraction_type,
+        staff_member_id,
+        target_user_id,
+        reason,
+        created_at
+    )
+SELECT
+    id,
+    guild_id,
+    'warn',
+    staff_member_id,
+    target_user_id,
+    reason,
+    created_at
+FROM
+    warn;
+
+SELECT
+    setval(
+        pg_get_serial_sequence('infraction', 'id'),
+        COALESCE(MAX(id), 0) + 1,
+        false
+    )
+FROM
+    infraction;
+
+INSERT INTO
+    infraction (
+        guild_id,
+        infraction_type,
+        target_user_id,
+        reason,
+        triggered_by,
+        created_at
+    )
+SELECT
+    guild_id,
+    action::text::infraction_type,
+    target_user_id,
+    'Automod: reached ' || warn_count || ' warns',
+    warn_id,
+    created_at
+FROM
+    automod_infraction
+ORDER BY
+    id;
+
+UPDATE infraction
+SET
+    case_number = numbered.case_number
+FROM
+    (
+        SELECT
+            id,
+            row_number() OVER (
+                PARTITION BY
+                    guild_id
+                ORDER BY
+                    created_at,
+                    id
+            ) AS case_number
+        FROM
+            infraction
+    ) numbered
+WHERE
+    infraction.id = numbered.id;
+
+ALTER TABLE infraction
+ALTER COLUMN case_number SET NOT NULL;
+
+INSERT INTO
+    guild_case_counter
+SELECT
+    guild_id,
+    MAX(case_number)
+FROM
+    infraction
+GROUP BY
+    guild_id;
+
+-- Assigns the next case number for the guild unless one was supplied, e.g. by an import.
+-- The counter row is locked by the upsert, so concurrent inserts in a guild never share a number.
+CREATE FUNCTION assign_case_number () RETURNS trigger AS $$
+BEGIN
+    IF NEW.case_number IS NULL THEN
+        INSERT INTO guild_case_counter (guild_id, last_case_number)
+        VALUES (NEW.guild_id, 1)
+        ON CONFLICT (guild_id) DO UPDATE
+        SET last_case_number = guild_case_counter.last_case_number + 1
+        RETURNING last_case_number INTO NEW.case_number;
+    ELSE
+        INSERT INTO guild_case_counter (guild_id, last_case_number)
+        VALUES (NEW.guild_id, NEW.case_number)
+        ON CONFLICT (guild_id) DO UPDATE
+        SET last_case_number = GREATEST(guild_case_counter.last_case_number, EXCLUDED.last_case_number);
+    END IF;
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER infraction_case_number BEFORE INSERT ON infraction FOR EACH ROW
+EXECUTE FUNCTION assign_case_number ();
+
+DROP TABLE automod_infraction;
+
+DROP TYPE automod_action;
+
+DROP TABLE warn;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX infraction_pending_expiry_idx;
+
+ALTER TABLE infraction
+DROP COLUMN lifted_at;
@@ -0,0 +1,8 @@
+-- Add up migration script here
+ALTER TABLE infraction
+ADD COLUMN lifted_at timestamp;
+
+CREATE INDEX infraction_pending_expiry_idx ON infraction (expires_at)
+WHERE
+    expires_at IS NOT NULL
+    AND lifted_at IS NULL;
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP VIEW warn;
+
+ALTER TABLE automod_settings
+DROP COLUMN warn_expiry_days;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+ALTER TABLE automod_settings
+ADD COLUMN warn_expiry_days int DEFAULT 0;
+
+-- Warns with whether they still count towards automod thresholds. A non-positive
+-- `warn_expiry_days` means warns never expire.
+CREATE VIEW warn AS
+SELECT
+    infraction.id,
+    infraction.guild_id,
+    infraction.case_number,
+    infraction.staff_member_id,
+    infraction.target_user_id,
+    infraction.reason,
+    infraction.created_at,
+    (
+        COALESCE(automod_settings.warn_expiry_days, 0) <= 0
+        OR infraction.created_at > LOCALTIMESTAMP - make_interval(days => automod_settings.warn_expiry_days)
+    ) AS active
+FROM
+    infraction
+    LEFT JOIN automod_settings ON automod_settings.guild_id = infraction.guild_id
+WHERE
+    infraction.infraction_type = 'warn';
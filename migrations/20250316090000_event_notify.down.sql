@@ -0,0 +1,12 @@
+-- Add down migration script here
+DROP TRIGGER automod_settings_notify ON automod_settings;
+
+DROP TRIGGER tickets_settings_notify ON tickets_settings;
+
+DROP TRIGGER logs_settings_notify ON logs_settings;
+
+DROP TRIGGER ticket_notify ON ticket;
+
+DROP TRIGGER infraction_notify ON infraction;
+
+DROP FUNCTION notify_event;
@@ -0,0 +1,72 @@
+-- Add up migration script here
+-- Publishes a change event for the row on the `server_events` channel. The payload only carries
+-- identifiers so it stays well under the NOTIFY size limit; subscribers fetch rows they need.
+-- A transaction that publishes its own, more specific events can skip these by setting
+-- `server_events.suppress_triggers` to `on` locally. Rows without a guild, such as tickets opened
+-- outside one, have no subscribers and publish nothing.
+CREATE FUNCTION notify_event () RETURNS trigger AS $$
+DECLARE
+    row_data jsonb;
+BEGIN
+    IF current_setting('server_events.suppress_triggers', true) = 'on' THEN
+        RETURN NULL;
+    END IF;
+
+    IF TG_OP = 'DELETE' THEN
+        row_data := to_jsonb(OLD);
+    ELSE
+        row_data := to_jsonb(NEW);
+    END IF;
+
+    IF row_data ->> 'guild_id' IS NULL THEN
+        RETURN NULL;
+    END IF;
+
+    PERFORM pg_notify(
+        'server_events',
+        json_build_object(
+            'kind', TG_ARGV[0],
+            'operation', lower(TG_OP),
+            'guild_id', (row_data ->> 'guild_id')::bigint,
+            'entity_id', (row_data ->> 'id')::bigint
+        )::text
+    );
+
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER infraction_notify
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON infraction FOR EACH ROW
+EXECUTE FUNCTION notify_event ('infraction');
+
+CREATE TRIGGER ticket_notify
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON ticket FOR EACH ROW
+EXECUTE FUNCTION notify_event ('ticket');
+
+CREATE TRIGGER logs_settings_notify
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON logs_settings FOR EACH ROW
+EXECUTE FUNCTION notify_event ('logs_settings');
+
+CREATE TRIGGER tickets_settings_notify
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON tickets_settings FOR EACH ROW
+EXECUTE FUNCTION notify_event ('tickets_settings');
+
+CREATE TRIGGER automod_settings_notify
+AFTER INSERT
+OR
+UPDATE
+OR DELETE ON automod_settings FOR EACH ROW
+EXECUTE FUNCTION notify_event ('automod_settings');
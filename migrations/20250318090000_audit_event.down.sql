@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE audit_event;
+
+DROP TYPE audit_event_type;
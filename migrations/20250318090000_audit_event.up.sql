@@ -0,0 +1,22 @@
+-- Add up migration script here
+CREATE TYPE audit_event_type AS ENUM (
+    'message_edit',
+    'message_delete',
+    'member_join',
+    'member_leave',
+    'member_role_update'
+);
+
+CREATE TABLE audit_event (
+    id bigserial PRIMARY KEY,
+    guild_id bigint NOT NULL REFERENCES guild (guild_id) ON DELETE CASCADE,
+    event_type audit_event_type NOT NULL,
+    user_id bigint NOT NULL,
+    channel_id bigint,
+    payload jsonb NOT NULL,
+    created_at timestamp NOT NULL DEFAULT NOW ()
+);
+
+CREATE INDEX audit_event_guild_idx ON audit_event (guild_id, created_at, id);
+
+CREATE INDEX audit_event_user_idx ON audit_event (guild_id, user_id, created_at, id);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+DROP TABLE logs_route;
+
+DROP TYPE log_category;
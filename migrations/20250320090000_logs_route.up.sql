@@ -0,0 +1,16 @@
+-- Add up migration script here
+CREATE TYPE log_category AS ENUM (
+    'messages',
+    'members',
+    'roles',
+    'moderation',
+    'tickets'
+);
+
+CREATE TABLE logs_route (
+    guild_id bigint REFERENCES guild (guild_id) ON DELETE CASCADE,
+    category log_category,
+    enabled boolean NOT NULL DEFAULT true,
+    channel_id bigint,
+    PRIMARY KEY (guild_id, category)
+);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TABLE logs_ignored_role;
+
+DROP TABLE logs_ignored_user;
+
+DROP TABLE logs_ignored_channel;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+CREATE TABLE logs_ignored_channel (
+    guild_id bigint REFERENCES guild (guild_id) ON DELETE CASCADE,
+    channel_id bigint,
+    PRIMARY KEY (guild_id, channel_id)
+);
+
+CREATE TABLE logs_ignored_user (
+    guild_id bigint REFERENCES guild (guild_id) ON DELETE CASCADE,
+    user_id bigint,
+    PRIMARY KEY (guild_id, user_id)
+);
+
+CREATE TABLE logs_ignored_role (
+    guild_id bigint REFERENCES guild (guild_id) ON DELETE CASCADE,
+    role_id bigint,
+    PRIMARY KEY (guild_id, role_id)
+);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP TABLE audit_prune_run;
+
+ALTER TABLE logs_settings
+DROP COLUMN retention_days;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+ALTER TABLE logs_settings
+ADD COLUMN retention_days int DEFAULT 0;
+
+CREATE TABLE audit_prune_run (
+    id serial PRIMARY KEY,
+    started_at timestamp NOT NULL DEFAULT NOW (),
+    finished_at timestamp,
+    rows_pruned bigint NOT NULL DEFAULT 0
+);
@@ -0,0 +1,10 @@
+-- Add down migration script here
+DROP INDEX audit_event_search_idx;
+
+ALTER TABLE audit_event
+DROP COLUMN search_vector;
+
+DROP INDEX ticket_search_idx;
+
+ALTER TABLE ticket
+DROP COLUMN search_vector;
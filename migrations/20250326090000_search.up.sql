@@ -0,0 +1,14 @@
+-- Add up migration script here
+ALTER TABLE ticket
+ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    setweight(to_tsvector('english', title), 'A') || setweight(to_tsvector('english', info), 'B')
+) STORED;
+
+CREATE INDEX ticket_search_idx ON ticket USING gin (search_vector);
+
+ALTER TABLE audit_event
+ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+    jsonb_to_tsvector('english', payload, '["string"]')
+) STORED;
+
+CREATE INDEX audit_event_search_idx ON audit_event USING gin (search_vector);
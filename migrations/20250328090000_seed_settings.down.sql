@@ -0,0 +1,2 @@
+-- Add down migration script here
+-- Seeded rows are indistinguishable from ones guilds configured themselves, so they are kept.
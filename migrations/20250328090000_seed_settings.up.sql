@@ -0,0 +1,24 @@
+-- Add up migration script here
+INSERT INTO
+    logs_settings (guild_id)
+SELECT
+    guild_id
+FROM
+    guild
+ON CONFLICT DO NOTHING;
+
+INSERT INTO
+    tickets_settings (guild_id)
+SELECT
+    guild_id
+FROM
+    guild
+ON CONFLICT DO NOTHING;
+
+INSERT INTO
+    automod_settings (guild_id)
+SELECT
+    guild_id
+FROM
+    guild
+ON CONFLICT DO NOTHING;
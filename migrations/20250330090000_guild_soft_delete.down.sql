@@ -0,0 +1,5 @@
+-- Add down migration script here
+DROP INDEX guild_deleted_at_idx;
+
+ALTER TABLE guild
+DROP COLUMN deleted_at;
@@ -0,0 +1,7 @@
+-- Add up migration script here
+ALTER TABLE guild
+ADD COLUMN deleted_at timestamp;
+
+CREATE INDEX guild_deleted_at_idx ON guild (deleted_at)
+WHERE
+    deleted_at IS NOT NULL;
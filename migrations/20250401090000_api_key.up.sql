@@ -0,0 +1,10 @@
+-- Add up migration script here
+CREATE TABLE api_key (
+    id serial PRIMARY KEY,
+    name text NOT NULL,
+    key_hash bytea NOT NULL UNIQUE,
+    scopes text[] NOT NULL,
+    guild_ids bigint[],
+    created_at timestamp NOT NULL DEFAULT NOW (),
+    revoked_at timestamp
+);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+DROP TABLE permission;
+
+DROP TABLE staff_role;
+
+DROP TYPE staff_action;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+CREATE TYPE staff_action AS ENUM (
+    'warn',
+    'timeout',
+    'kick',
+    'ban',
+    'unban',
+    'note',
+    'manage_warns',
+    'manage_tickets',
+    'delete_tickets'
+);
+
+CREATE TABLE staff_role (
+    guild_id bigint REFERENCES guild (guild_id) ON DELETE CASCADE,
+    role_id bigint,
+    PRIMARY KEY (guild_id, role_id)
+);
+
+CREATE TABLE permission (
+    guild_id bigint,
+    role_id bigint,
+    action staff_action,
+    PRIMARY KEY (guild_id, role_id, action),
+    FOREIGN KEY (guild_id, role_id) REFERENCES staff_role (guild_id, role_id) ON DELETE CASCADE
+);
@@ -0,0 +1,61 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{
+    logs::{AuditEvent, LogIgnoreKind, LogsRoute, LogsSettings},
+    moderation::{AutomodSettings, Infraction},
+    staff::RolePermissions,
+    tickets::{Ticket, TicketHistoryEntry, TicketMessage, TicketsSettings},
+};
+
+/// Version of the archive layout, bumped whenever a record changes incompatibly.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Archives are sent to clients in chunks of roughly this many bytes.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Longest single record accepted on import. Lines are buffered until complete, so this bounds
+/// the memory an import can use.
+pub const MAX_LINE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Largest archive accepted on import, since it is applied in a single transaction.
+pub const MAX_ARCHIVE_SIZE: usize = 1024 * 1024 * 1024;
+
+/// One line of a guild archive. The first line is always a [`Record::Header`], and records
+/// always come after any record they reference, e.g. a ticket before its messages.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "snake_case")]
+pub enum Record {
+    Header {
+        schema_version: u32,
+        guild_id: i64,
+        exported_at: NaiveDateTime,
+    },
+    LogsSettings(LogsSettings),
+    LogsRoute(LogsRoute),
+    LogIgnore {
+        kind: LogIgnoreKind,
+        id: i64,
+    },
+    TicketsSettings(TicketsSettings),
+    AutomodSettings(AutomodSettings),
+    StaffRole(RolePermissions),
+    Ticket(Ticket),
+    TicketMessage(TicketMessage),
+    TicketHistory(TicketHistoryEntry),
+    Infraction(Infraction),
+    AuditEvent(AuditEvent),
+}
+
+/// Appends `record` to `buffer` as a single JSON line.
+pub fn write_record(buffer: &mut Vec<u8>, record: &Record) -> serde_json::Result<()> {
+    serde_json::to_writer(&mut *buffer, record)?;
+    buffer.push(b'\n');
+
+    Ok(())
+}
+
+/// Parses a single line written by [`write_record`].
+pub fn read_record(line: &[u8]) -> serde_json::Result<Record> {
+    serde_json::from_slice(line)
+}
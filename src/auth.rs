@@ -0,0 +1,496 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use tonic::{service::Interceptor, Request, Status};
+use tracing::{debug, warn};
+
+use crate::models::admin::ApiKey;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scope {
+    GuildRead,
+    GuildWrite,
+    LogsRead,
+    LogsWrite,
+    ModerationRead,
+    ModerationWrite,
+    TicketsRead,
+    TicketsWrite,
+    EventsRead,
+    Admin,
+}
+
+impl Scope {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "guild:read" => Some(Scope::GuildRead),
+            "guild:write" => Some(Scope::GuildWrite),
+            "logs:read" => Some(Scope::LogsRead),
+            "logs:write" => Some(Scope::LogsWrite),
+            "moderation:read" => Some(Scope::ModerationRead),
+            "moderation:write" => Some(Scope::ModerationWrite),
+            "tickets:read" => Some(Scope::TicketsRead),
+            "tickets:write" => Some(Scope::TicketsWrite),
+            "events:read" => Some(Scope::EventsRead),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::GuildRead => "guild:read",
+            Scope::GuildWrite => "guild:write",
+            Scope::LogsRead => "logs:read",
+            Scope::LogsWrite => "logs:write",
+            Scope::ModerationRead => "moderation:read",
+            Scope::ModerationWrite => "moderation:write",
+            Scope::TicketsRead => "tickets:read",
+            Scope::TicketsWrite => "tickets:write",
+            Scope::EventsRead => "events:read",
+            Scope::Admin => "admin",
+        }
+    }
+
+    /// The write scope that also grants this read scope, if any.
+    fn implied_by(self) -> Option<Self> {
+        match self {
+            Scope::GuildRead => Some(Scope::GuildWrite),
+            Scope::LogsRead => Some(Scope::LogsWrite),
+            Scope::ModerationRead => Some(Scope::ModerationWrite),
+            Scope::TicketsRead => Some(Scope::TicketsWrite),
+            _ => None,
+        }
+    }
+}
+
+/// Identity of the client certificate presented over mutual TLS, kept for auditing.
+#[derive(Debug, Clone)]
+pub struct PeerIdentity {
+    pub subject: String,
+    pub serial: String,
+}
+
+impl PeerIdentity {
+    fn from_request(request: &Request<()>) -> Option<Self> {
+        let certs = request.peer_certs()?;
+        // The client's own certificate comes first, followed by any intermediates.
+        let cert = certs.first()?;
+
+        match x509_parser::parse_x509_certificate(cert.as_ref()) {
+            Ok((_, cert)) => Some(Self {
+                subject: cert.subject().to_string(),
+                serial: cert.raw_serial_as_string(),
+            }),
+            Err(error) => {
+                warn!("failed to parse peer certificate: {}", error);
+                None
+            }
+        }
+    }
+}
+
+impl fmt::Display for PeerIdentity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (serial {})", self.subject, self.serial)
+    }
+}
+
+/// The authenticated client behind a request, added to its extensions by [`BearerAuth`].
+#[derive(Debug, Clone)]
+pub struct Caller {
+    /// The API key the request was made with, or `None` for a token from `API_TOKENS`.
+    pub api_key_id: Option<i32>,
+    /// The client certificate, when the connection uses mutual TLS.
+    pub peer: Option<PeerIdentity>,
+    /// `None` grants every scope.
+    scopes: Option<HashSet<Scope>>,
+    /// `None` grants every guild.
+    guild_ids: Option<HashSet<i64>>,
+}
+
+impl Caller {
+    fn unrestricted() -> Self {
+        Self {
+            api_key_id: None,
+            peer: None,
+            scopes: None,
+            guild_ids: None,
+        }
+    }
+
+    fn from_api_key(api_key: &ApiKey) -> Self {
+        Self {
+            api_key_id: Some(api_key.id),
+            peer: None,
+            scopes: Some(
+                api_key
+                    .scopes
+                    .iter()
+                    .filter_map(|scope| Scope::parse(scope))
+                    .collect(),
+            ),
+            guild_ids: api_key
+                .guild_ids
+                .as_ref()
+                .map(|guild_ids| guild_ids.iter().copied().collect()),
+        }
+    }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        match &self.scopes {
+            Some(scopes) => {
+                scopes.contains(&scope)
+                    || scope
+                        .implied_by()
+                        .is_some_and(|implied_by| scopes.contains(&implied_by))
+            }
+            None => true,
+        }
+    }
+
+    /// Whether the caller may act on `guild_id`, or on every guild at once when it is `None`.
+    pub fn can_access_guild(&self, guild_id: Option<i64>) -> bool {
+        match (&self.guild_ids, guild_id) {
+            (None, _) => true,
+            (Some(guild_ids), Some(guild_id)) => guild_ids.contains(&guild_id),
+            (Some(_), None) => false,
+        }
+    }
+
+    pub fn authorize(&self, scope: Scope, guild_id: Option<i64>) -> Result<(), Status> {
+        if !self.has_scope(scope) {
+            debug!("denied {} the `{}` scope", self, scope.as_str());
+            return Err(Status::permission_denied(format!(
+                "API key lacks the `{}` scope",
+                scope.as_str()
+            )));
+        }
+
+        if !self.can_access_guild(guild_id) {
+            debug!("denied {} access to guild {:?}", self, guild_id);
+            return Err(match guild_id {
+                Some(guild_id) => {
+                    Status::permission_denied(format!("API key may not access guild {}", guild_id))
+                }
+                None => Status::permission_denied("API key is limited to specific guilds"),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Caller {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.api_key_id {
+            Some(api_key_id) => write!(f, "API key {}", api_key_id)?,
+            None => write!(f, "server token")?,
+        }
+
+        match &self.peer {
+            Some(peer) => write!(f, " presenting certificate {}", peer),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn caller<T>(request: &Request<T>) -> Result<&Caller, Status> {
+    match request.extensions().get::<Caller>() {
+        Some(caller) => Ok(caller),
+        None => Err(Status::unauthenticated("request was not authenticated")),
+    }
+}
+
+/// Checks the caller of `request` may use `scope` on `guild_id`, where `None` means a request
+/// spanning every guild.
+pub fn authorize<T>(
+    request: &Request<T>,
+    scope: Scope,
+    guild_id: Option<i64>,
+) -> Result<(), Status> {
+    caller(request)?.authorize(scope, guild_id)
+}
+
+/// Active API keys by the SHA-256 hash of their token. The cache is reloaded periodically, so keys
+/// created or revoked through another replica take effect here too.
+#[derive(Debug, Clone, Default)]
+pub struct ApiKeyCache {
+    keys: Arc<RwLock<HashMap<Vec<u8>, Caller>>>,
+}
+
+impl ApiKeyCache {
+    pub async fn refresh(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let query = "SELECT * FROM api_key WHERE revoked_at IS NULL";
+        let api_keys = sqlx::query_as::<_, ApiKey>(query).fetch_all(pool).await?;
+
+        let keys = api_keys
+            .iter()
+            .map(|api_key| (api_key.key_hash.clone(), Caller::from_api_key(api_key)))
+            .collect();
+
+        *self.keys.write().unwrap_or_else(PoisonError::into_inner) = keys;
+
+        Ok(())
+    }
+
+    fn get(&self, token: &str) -> Option<Caller> {
+        self.keys
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&hash_token(token))
+            .cloned()
+    }
+}
+
+pub fn hash_token(token: &str) -> Vec<u8> {
+    Sha256::digest(token.as_bytes()).to_vec()
+}
+
+/// Generates a new random API key token. Only its hash is stored.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Rejects requests whose `authorization` metadata does not carry one of the configured bearer
+/// tokens or an active API key, and records the [`Caller`] on the ones it lets through.
+#[derive(Clone)]
+pub struct BearerAuth {
+    tokens: Arc<HashSet<String>>,
+    api_keys: ApiKeyCache,
+}
+
+impl BearerAuth {
+    /// Reads the accepted tokens from `API_TOKENS`, a comma-separated list. These tokens have
+    /// every scope, so they can be used to create the first API keys.
+    pub fn from_env(api_keys: ApiKeyCache) -> Self {
+        let tokens = std::env::var("API_TOKENS").expect("API_TOKENS must be set.");
+        let tokens = tokens
+            .split(',')
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .map(String::from)
+            .collect::<HashSet<_>>();
+
+        if tokens.is_empty() {
+            panic!("API_TOKENS must contain at least one token.");
+        }
+
+        Self {
+            tokens: Arc::new(tokens),
+            api_keys,
+        }
+    }
+
+    fn accepts(&self, token: &str) -> bool {
+        // Every token is compared in full so the time taken doesn't reveal how much matched.
+        self.tokens.iter().fold(false, |accepted, candidate| {
+            constant_time_eq(candidate.as_bytes(), token.as_bytes()) | accepted
+        })
+    }
+
+    fn authenticate(&self, token: &str) -> Option<Caller> {
+        if self.accepts(token) {
+            return Some(Caller::unrestricted());
+        }
+
+        self.api_keys.get(token)
+    }
+}
+
+impl Interceptor for BearerAuth {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        let token = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        let mut caller = match token {
+            Some(token) => match self.authenticate(token.trim()) {
+                Some(caller) => caller,
+                None => {
+                    debug!("rejected request with an invalid bearer token");
+                    return Err(Status::unauthenticated("invalid bearer token"));
+                }
+            },
+            None => return Err(Status::unauthenticated("missing bearer token")),
+        };
+
+        caller.peer = PeerIdentity::from_request(&request);
+        debug!("authenticated request from {}", caller);
+
+        request.extensions_mut().insert(caller);
+
+        Ok(request)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    a.iter()
+        .zip(b)
+        .fold(0, |difference, (a, b)| difference | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth() -> BearerAuth {
+        BearerAuth {
+            tokens: Arc::new(HashSet::from([String::from("server-token")])),
+            api_keys: ApiKeyCache::default(),
+        }
+    }
+
+    fn api_key(scopes: &[&str], guild_ids: Option<Vec<i64>>) -> ApiKey {
+        ApiKey {
+            id: 1,
+            name: String::from("test"),
+            key_hash: hash_token("api-key"),
+            scopes: scopes.iter().map(|scope| String::from(*scope)).collect(),
+            guild_ids,
+            created_at: chrono::NaiveDateTime::default(),
+            revoked_at: None,
+        }
+    }
+
+    fn request(authorization: Option<&str>) -> Request<()> {
+        let mut request = Request::new(());
+        if let Some(authorization) = authorization {
+            request
+                .metadata_mut()
+                .insert("authorization", authorization.parse().unwrap());
+        }
+        request
+    }
+
+    #[test]
+    fn accepts_a_configured_token() {
+        let request = auth().call(request(Some("Bearer server-token"))).unwrap();
+        let caller = caller(&request).unwrap();
+
+        assert_eq!(caller.api_key_id, None);
+        assert!(caller.has_scope(Scope::Admin));
+        assert!(caller.can_access_guild(None));
+    }
+
+    #[test]
+    fn rejects_a_missing_header() {
+        let status = auth().call(request(None)).unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert_eq!(status.message(), "missing bearer token");
+    }
+
+    #[test]
+    fn rejects_a_missing_or_wrong_prefix() {
+        for authorization in [
+            "server-token",
+            "Basic server-token",
+            "Bearer",
+            "Bearerserver-token",
+        ] {
+            let status = auth().call(request(Some(authorization))).unwrap_err();
+
+            assert_eq!(status.code(), tonic::Code::Unauthenticated);
+            assert_eq!(status.message(), "missing bearer token");
+        }
+    }
+
+    #[test]
+    fn rejects_an_unknown_token() {
+        for authorization in ["Bearer wrong-token", "Bearer server-token-2", "Bearer "] {
+            let status = auth().call(request(Some(authorization))).unwrap_err();
+
+            assert_eq!(status.code(), tonic::Code::Unauthenticated);
+            assert_eq!(status.message(), "invalid bearer token");
+        }
+    }
+
+    #[test]
+    fn constant_time_eq_compares_whole_values() {
+        assert!(constant_time_eq(b"token", b"token"));
+        assert!(!constant_time_eq(b"token", b"tokem"));
+        assert!(!constant_time_eq(b"token", b"token2"));
+        assert!(!constant_time_eq(b"", b"token"));
+    }
+
+    #[test]
+    fn allows_a_granted_scope() {
+        let caller = Caller::from_api_key(&api_key(&["tickets:read"], None));
+
+        assert!(caller.authorize(Scope::TicketsRead, Some(1)).is_ok());
+        assert!(caller.authorize(Scope::TicketsRead, None).is_ok());
+    }
+
+    #[test]
+    fn write_scope_grants_read() {
+        let caller = Caller::from_api_key(&api_key(&["moderation:write"], None));
+
+        assert!(caller.authorize(Scope::ModerationWrite, Some(1)).is_ok());
+        assert!(caller.authorize(Scope::ModerationRead, Some(1)).is_ok());
+    }
+
+    #[test]
+    fn denies_a_missing_scope() {
+        let caller = Caller::from_api_key(&api_key(&["tickets:read", "unknown"], None));
+
+        for scope in [Scope::TicketsWrite, Scope::LogsRead, Scope::Admin] {
+            let status = caller.authorize(scope, Some(1)).unwrap_err();
+
+            assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        }
+    }
+
+    #[test]
+    fn limits_a_key_to_its_guilds() {
+        let caller = Caller::from_api_key(&api_key(&["logs:read"], Some(vec![1, 2])));
+
+        assert!(caller.authorize(Scope::LogsRead, Some(1)).is_ok());
+        assert!(caller.authorize(Scope::LogsRead, Some(2)).is_ok());
+
+        let status = caller.authorize(Scope::LogsRead, Some(3)).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+
+    #[test]
+    fn denies_a_guild_limited_key_without_a_guild() {
+        let caller = Caller::from_api_key(&api_key(&["logs:read"], Some(vec![1])));
+
+        let status = caller.authorize(Scope::LogsRead, None).unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+        assert_eq!(status.message(), "API key is limited to specific guilds");
+    }
+
+    #[test]
+    fn authenticates_an_active_api_key() {
+        let auth = auth();
+        auth.api_keys.keys.write().unwrap().insert(
+            hash_token("api-key"),
+            Caller::from_api_key(&api_key(&["tickets:read"], None)),
+        );
+
+        let request = auth.clone().call(request(Some("Bearer api-key"))).unwrap();
+        let caller = caller(&request).unwrap();
+
+        assert_eq!(caller.api_key_id, Some(1));
+        assert!(caller.has_scope(Scope::TicketsRead));
+        assert!(!caller.has_scope(Scope::TicketsWrite));
+    }
+}
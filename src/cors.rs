@@ -0,0 +1,38 @@
+use tonic::codegen::http::{header, HeaderName, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::info;
+
+/// Builds the CORS policy from `CORS_ALLOWED_ORIGINS`, a comma-separated list of origins such as
+/// `https://dashboard.example.com` whose pages may call the API from a browser. Without it no
+/// cross-origin requests are allowed, which the bot itself never needs.
+pub fn layer_from_env() -> Result<CorsLayer, Box<dyn std::error::Error>> {
+    let origins = std::env::var("CORS_ALLOWED_ORIGINS").unwrap_or_default();
+    let origins = origins
+        .split(',')
+        .map(str::trim)
+        .filter(|origin| !origin.is_empty())
+        .map(HeaderValue::from_str)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if origins.is_empty() {
+        return Ok(CorsLayer::new());
+    }
+
+    info!("allowing cross-origin requests from {:?}", origins);
+
+    Ok(CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([Method::POST])
+        .allow_headers([
+            header::AUTHORIZATION,
+            header::CONTENT_TYPE,
+            HeaderName::from_static("grpc-timeout"),
+            HeaderName::from_static("x-grpc-web"),
+            HeaderName::from_static("x-user-agent"),
+        ])
+        .expose_headers([
+            HeaderName::from_static("grpc-status"),
+            HeaderName::from_static("grpc-message"),
+            HeaderName::from_static("grpc-status-details-bin"),
+        ]))
+}
@@ -0,0 +1,80 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgExecutor;
+use tokio::sync::broadcast;
+
+/// Postgres channel that carries events between server replicas.
+pub const CHANNEL: &str = "server_events";
+
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    Infraction,
+    InfractionExpired,
+    Ticket,
+    LogsSettings,
+    TicketsSettings,
+    AutomodSettings,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Insert,
+    Update,
+    Delete,
+}
+
+/// A change to a guild's data. Most events are emitted by table triggers, so they carry only
+/// identifiers; `entity_id` is absent for per-guild rows such as settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Event {
+    pub kind: EventKind,
+    pub operation: Operation,
+    pub guild_id: i64,
+    pub entity_id: Option<i64>,
+}
+
+/// Fan-out of events received from Postgres to the streaming subscribers of this replica.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Event>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { sender }
+    }
+
+    pub fn publish(&self, event: Event) {
+        // Sending only fails when nobody is subscribed, in which case the event has no audience.
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sends an event to every replica, including this one, through Postgres. Inside a transaction,
+/// the event is only delivered once it commits.
+pub async fn notify<'e>(executor: impl PgExecutor<'e>, event: &Event) -> Result<(), sqlx::Error> {
+    let payload =
+        serde_json::to_string(event).map_err(|error| sqlx::Error::Encode(error.into()))?;
+
+    sqlx::query("SELECT pg_notify($1, $2)")
+        .bind(CHANNEL)
+        .bind(payload)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
@@ -1,15 +1,29 @@
+// Every handler reports failures as `tonic::Status`, which is large by design of the gRPC API.
+#![allow(clippy::result_large_err)]
+
 use dotenv::dotenv;
 use services::{
+    admin_service::{self, proto::admin_service_server::AdminServiceServer},
+    events_service::{self, proto::events_service_server::EventsServiceServer},
     guild_service::{self, proto::guild_service_server::GuildServiceServer},
     logs_service::{self, proto::logs_service_server::LogsServiceServer},
     moderation_service::{self, proto::moderation_service_server::ModerationServiceServer},
     tickets_service::{self, proto::tickets_service_server::TicketsServiceServer},
 };
 use sqlx::postgres::PgPoolOptions;
-use tonic::transport::Server;
+use tonic::{service::interceptor::InterceptedService, transport::Server};
 
+mod archive;
+mod auth;
+mod cors;
+mod events;
 mod models;
+mod pagination;
+mod permissions;
 mod services;
+mod tasks;
+mod tls;
+mod transcript;
 mod utils;
 
 #[tokio::main]
@@ -26,25 +40,99 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await
         .expect("Failed to connect to the database with provided DATABASE_URL.");
 
-    let addr = "[::1]:50051".parse()?;
+    // Clients on other hosts need a non-loopback address, ideally together with TLS.
+    let addr = std::env::var("BIND_ADDRESS")
+        .unwrap_or_else(|_| String::from("[::1]:50051"))
+        .parse()?;
+    let tls_config = tls::config_from_env()?;
+    let cors = cors::layer_from_env()?;
+
+    let guild_purge_grace_days = match std::env::var("GUILD_PURGE_GRACE_DAYS") {
+        Ok(days) => days
+            .parse::<i32>()
+            .expect("GUILD_PURGE_GRACE_DAYS must be a whole number of days."),
+        Err(_) => 30,
+    };
+    assert!(
+        guild_purge_grace_days >= 0,
+        "GUILD_PURGE_GRACE_DAYS must not be negative."
+    );
+
+    let api_keys = auth::ApiKeyCache::default();
+    api_keys
+        .refresh(&pool)
+        .await
+        .expect("Failed to load API keys from the database.");
+
+    let auth = auth::BearerAuth::from_env(api_keys.clone());
+
+    // Reflection only describes the API, so it stays open unless explicitly locked down.
+    let reflection_requires_auth = match std::env::var("REFLECTION_REQUIRES_AUTH") {
+        Ok(value) => value
+            .parse::<bool>()
+            .expect("REFLECTION_REQUIRES_AUTH must be true or false."),
+        Err(_) => false,
+    };
+
+    let events = events::EventBus::new();
+
+    tokio::spawn(tasks::api_keys::run(pool.clone(), api_keys.clone()));
+    tokio::spawn(tasks::listener::run(pool.clone(), events.clone()));
+    tokio::spawn(tasks::expiry::run(pool.clone()));
+    tokio::spawn(tasks::prune::run(pool.clone()));
+    tokio::spawn(tasks::purge::run(pool.clone(), guild_purge_grace_days));
 
     let service = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(admin_service::proto::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(events_service::proto::FILE_DESCRIPTOR_SET)
         .register_encoded_file_descriptor_set(guild_service::proto::FILE_DESCRIPTOR_SET)
         .register_encoded_file_descriptor_set(logs_service::proto::FILE_DESCRIPTOR_SET)
         .register_encoded_file_descriptor_set(moderation_service::proto::FILE_DESCRIPTOR_SET)
         .register_encoded_file_descriptor_set(tickets_service::proto::FILE_DESCRIPTOR_SET)
         .build_v1()?;
 
-    let guild_service = GuildServiceServer::new(guild_service::GuildService::new(pool.clone()));
-    let logs_service = LogsServiceServer::new(logs_service::LogsService::new(pool.clone()));
-    let moderation_service =
-        ModerationServiceServer::new(moderation_service::ModerationService::new(pool.clone()));
-    let tickets_serivce =
-        TicketsServiceServer::new(tickets_service::TicketsService::new(pool.clone()));
+    let (open_reflection_service, authenticated_reflection_service) = if reflection_requires_auth {
+        (None, Some(InterceptedService::new(service, auth.clone())))
+    } else {
+        (Some(service), None)
+    };
+
+    let admin_service = AdminServiceServer::with_interceptor(
+        admin_service::AdminService::new(pool.clone(), api_keys),
+        auth.clone(),
+    );
+    let events_service = EventsServiceServer::with_interceptor(
+        events_service::EventsService::new(events.clone()),
+        auth.clone(),
+    );
+    let guild_service = GuildServiceServer::with_interceptor(
+        guild_service::GuildService::new(pool.clone()),
+        auth.clone(),
+    );
+    let logs_service = LogsServiceServer::with_interceptor(
+        logs_service::LogsService::new(pool.clone()),
+        auth.clone(),
+    );
+    let moderation_service = ModerationServiceServer::with_interceptor(
+        moderation_service::ModerationService::new(pool.clone(), events.clone()),
+        auth.clone(),
+    );
+    let tickets_serivce = TicketsServiceServer::with_interceptor(
+        tickets_service::TicketsService::new(pool.clone()),
+        auth,
+    );
+
+    let mut server = Server::builder();
+    if let Some(tls_config) = tls_config {
+        server = server.tls_config(tls_config)?;
+    }
 
-    Server::builder()
-        .layer(tower_http::cors::CorsLayer::permissive())
-        .add_service(service)
+    server
+        .layer(cors)
+        .add_optional_service(open_reflection_service)
+        .add_optional_service(authenticated_reflection_service)
+        .add_service(admin_service)
+        .add_service(events_service)
         .add_service(guild_service)
         .add_service(logs_service)
         .add_service(moderation_service)
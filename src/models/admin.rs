@@ -0,0 +1,12 @@
+/// A scoped API key. Only the SHA-256 hash of its token is stored, and a missing list of guild
+/// IDs means the key may act on every guild.
+#[derive(sqlx::FromRow)]
+pub struct ApiKey {
+    pub id: i32,
+    pub name: String,
+    pub key_hash: Vec<u8>,
+    pub scopes: Vec<String>,
+    pub guild_ids: Option<Vec<i64>>,
+    pub created_at: chrono::NaiveDateTime,
+    pub revoked_at: Option<chrono::NaiveDateTime>,
+}
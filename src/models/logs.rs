@@ -1,6 +1,158 @@
-#[derive(sqlx::FromRow)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, sqlx::FromRow)]
 pub struct LogsSettings {
     pub guild_id: i64,
     pub enabled: bool,
     pub channel_id: i64,
+    pub retention_days: i32,
+}
+
+impl LogsSettings {
+    /// Settings for a guild without a `logs_settings` row, matching the column defaults.
+    pub fn default_for(guild_id: i64) -> Self {
+        Self {
+            guild_id,
+            enabled: false,
+            channel_id: 0,
+            retention_days: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "log_category", rename_all = "snake_case")]
+pub enum LogCategory {
+    Messages,
+    Members,
+    Roles,
+    Moderation,
+    Tickets,
+}
+
+/// Overrides where, and whether, one category of a guild's logs is sent. A missing channel falls
+/// back to the default channel in `logs_settings`.
+#[derive(Serialize, Deserialize, sqlx::FromRow)]
+pub struct LogsRoute {
+    pub guild_id: i64,
+    pub category: LogCategory,
+    pub enabled: bool,
+    pub channel_id: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogIgnoreKind {
+    Channel,
+    User,
+    Role,
+}
+
+impl LogIgnoreKind {
+    pub fn table(self) -> &'static str {
+        match self {
+            LogIgnoreKind::Channel => "logs_ignored_channel",
+            LogIgnoreKind::User => "logs_ignored_user",
+            LogIgnoreKind::Role => "logs_ignored_role",
+        }
+    }
+
+    pub fn column(self) -> &'static str {
+        match self {
+            LogIgnoreKind::Channel => "channel_id",
+            LogIgnoreKind::User => "user_id",
+            LogIgnoreKind::Role => "role_id",
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+pub struct ResolvedLogChannel {
+    pub enabled: bool,
+    pub channel_id: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "audit_event_type", rename_all = "snake_case")]
+pub enum AuditEventType {
+    MessageEdit,
+    MessageDelete,
+    MemberJoin,
+    MemberLeave,
+    MemberRoleUpdate,
+}
+
+/// Event-specific data, stored as `jsonb` alongside the indexed columns of `audit_event`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditPayload {
+    MessageEdit {
+        channel_id: i64,
+        message_id: i64,
+        before: String,
+        after: String,
+    },
+    MessageDelete {
+        channel_id: i64,
+        message_id: i64,
+        content: String,
+        attachment_urls: Vec<String>,
+    },
+    MemberJoin {
+        account_created_at: i64,
+    },
+    MemberLeave {
+        role_ids: Vec<i64>,
+    },
+    MemberRoleUpdate {
+        added_role_ids: Vec<i64>,
+        removed_role_ids: Vec<i64>,
+    },
+}
+
+impl AuditPayload {
+    pub fn event_type(&self) -> AuditEventType {
+        match self {
+            AuditPayload::MessageEdit { .. } => AuditEventType::MessageEdit,
+            AuditPayload::MessageDelete { .. } => AuditEventType::MessageDelete,
+            AuditPayload::MemberJoin { .. } => AuditEventType::MemberJoin,
+            AuditPayload::MemberLeave { .. } => AuditEventType::MemberLeave,
+            AuditPayload::MemberRoleUpdate { .. } => AuditEventType::MemberRoleUpdate,
+        }
+    }
+
+    pub fn channel_id(&self) -> Option<i64> {
+        match self {
+            AuditPayload::MessageEdit { channel_id, .. }
+            | AuditPayload::MessageDelete { channel_id, .. } => Some(*channel_id),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, sqlx::FromRow)]
+pub struct AuditEvent {
+    pub id: i64,
+    pub guild_id: i64,
+    pub event_type: AuditEventType,
+    pub user_id: i64,
+    pub channel_id: Option<i64>,
+    pub payload: sqlx::types::Json<AuditPayload>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct AuditEventSearchHit {
+    #[sqlx(flatten)]
+    pub event: AuditEvent,
+    pub rank: f32,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct AuditPruneRun {
+    pub started_at: chrono::NaiveDateTime,
+    pub finished_at: Option<chrono::NaiveDateTime>,
+    pub rows_pruned: i64,
 }
@@ -1,3 +1,5 @@
+pub mod admin;
 pub mod logs;
 pub mod moderation;
+pub mod staff;
 pub mod tickets;
@@ -1,18 +1,144 @@
-#[derive(sqlx::FromRow)]
+#[derive(serde::Serialize, serde::Deserialize, sqlx::FromRow)]
 pub struct AutomodSettings {
     pub guild_id: i64,
     pub autoban_enabled: bool,
     pub autoban_threshold: i32,
     pub autokick_enabled: bool,
     pub autokick_threshold: i32,
+    pub warn_expiry_days: i32,
+}
+
+impl AutomodSettings {
+    /// Settings for a guild without an `automod_settings` row, matching the column defaults.
+    pub fn default_for(guild_id: i64) -> Self {
+        Self {
+            guild_id,
+            autoban_enabled: false,
+            autoban_threshold: 5,
+            autokick_enabled: false,
+            autokick_threshold: 3,
+            warn_expiry_days: 0,
+        }
+    }
+
+    /// The action the guild's automod configuration calls for once a user has `warn_count` active
+    /// warns.
+    /// Bans take precedence over kicks when both thresholds are reached.
+    pub fn escalation(&self, warn_count: i64) -> Option<AutomodAction> {
+        if self.autoban_enabled && warn_count >= i64::from(self.autoban_threshold) {
+            Some(AutomodAction::Ban)
+        } else if self.autokick_enabled && warn_count >= i64::from(self.autokick_threshold) {
+            Some(AutomodAction::Kick)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutomodAction {
+    Kick,
+    Ban,
+}
+
+impl From<AutomodAction> for InfractionType {
+    fn from(value: AutomodAction) -> Self {
+        match value {
+            AutomodAction::Kick => InfractionType::Kick,
+            AutomodAction::Ban => InfractionType::Ban,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "infraction_type", rename_all = "snake_case")]
+pub enum InfractionType {
+    Warn,
+    Timeout,
+    Kick,
+    Ban,
+    Unban,
+    Note,
+}
+
+impl InfractionType {
+    /// Whether infractions of this type may carry an expiry.
+    pub fn is_temporary(self) -> bool {
+        matches!(self, InfractionType::Timeout | InfractionType::Ban)
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct Infraction {
+    pub id: i32,
+    pub guild_id: i64,
+    pub case_number: i32,
+    pub infraction_type: InfractionType,
+    pub staff_member_id: Option<i64>,
+    pub target_user_id: i64,
+    pub reason: String,
+    pub expires_at: Option<chrono::NaiveDateTime>,
+    pub triggered_by: Option<i32>,
+    pub created_at: chrono::NaiveDateTime,
+    pub lifted_at: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(sqlx::FromRow)]
 pub struct Warn {
     pub id: i32,
     pub guild_id: i64,
+    pub case_number: i32,
     pub staff_member_id: i64,
-    pub targer_user_id: i64,
+    pub target_user_id: i64,
     pub reason: String,
     pub created_at: chrono::NaiveDateTime,
+    pub active: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(autoban_enabled: bool, autokick_enabled: bool) -> AutomodSettings {
+        AutomodSettings {
+            autoban_enabled,
+            autokick_enabled,
+            ..AutomodSettings::default_for(1)
+        }
+    }
+
+    #[test]
+    fn escalates_exactly_at_each_threshold() {
+        let settings = settings(true, true);
+
+        assert_eq!(settings.escalation(2), None);
+        assert_eq!(settings.escalation(3), Some(AutomodAction::Kick));
+        assert_eq!(settings.escalation(4), Some(AutomodAction::Kick));
+        assert_eq!(settings.escalation(5), Some(AutomodAction::Ban));
+    }
+
+    #[test]
+    fn bans_take_precedence_over_kicks() {
+        let settings = AutomodSettings {
+            autoban_threshold: 3,
+            ..settings(true, true)
+        };
+
+        assert_eq!(settings.escalation(3), Some(AutomodAction::Ban));
+    }
+
+    #[test]
+    fn disabled_actions_never_trigger() {
+        assert_eq!(settings(false, false).escalation(100), None);
+        assert_eq!(
+            settings(false, true).escalation(5),
+            Some(AutomodAction::Kick)
+        );
+        assert_eq!(settings(true, false).escalation(4), None);
+        assert_eq!(
+            settings(true, false).escalation(5),
+            Some(AutomodAction::Ban)
+        );
+    }
 }
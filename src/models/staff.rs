@@ -0,0 +1,38 @@
+use super::moderation::InfractionType;
+
+/// Something a staff member may be permitted to do, granted to roles through `permission`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "staff_action", rename_all = "snake_case")]
+pub enum StaffAction {
+    Warn,
+    Timeout,
+    Kick,
+    Ban,
+    Unban,
+    Note,
+    /// Editing or deleting existing warns.
+    ManageWarns,
+    /// Claiming, holding, reopening and closing tickets.
+    ManageTickets,
+    DeleteTickets,
+}
+
+impl From<InfractionType> for StaffAction {
+    fn from(value: InfractionType) -> Self {
+        match value {
+            InfractionType::Warn => StaffAction::Warn,
+            InfractionType::Timeout => StaffAction::Timeout,
+            InfractionType::Kick => StaffAction::Kick,
+            InfractionType::Ban => StaffAction::Ban,
+            InfractionType::Unban => StaffAction::Unban,
+            InfractionType::Note => StaffAction::Note,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct RolePermissions {
+    pub role_id: i64,
+    pub actions: Vec<StaffAction>,
+}
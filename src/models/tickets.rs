@@ -1,11 +1,45 @@
-#[derive(sqlx::FromRow)]
+#[derive(serde::Serialize, serde::Deserialize, sqlx::FromRow)]
 pub struct TicketsSettings {
     pub guild_id: i64,
     pub enabled: bool,
     pub channel_id: i64,
 }
 
-#[derive(sqlx::FromRow)]
+impl TicketsSettings {
+    /// Settings for a guild without a `tickets_settings` row, matching the column defaults.
+    pub fn default_for(guild_id: i64) -> Self {
+        Self {
+            guild_id,
+            enabled: false,
+            channel_id: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "ticket_status", rename_all = "snake_case")]
+pub enum TicketStatus {
+    Open,
+    Claimed,
+    OnHold,
+    Closed,
+}
+
+impl TicketStatus {
+    /// Whether a ticket in this status may move to `to`.
+    pub fn can_transition_to(self, to: TicketStatus) -> bool {
+        matches!(
+            (self, to),
+            (Self::Open | Self::OnHold, Self::Claimed)
+                | (Self::Open | Self::Claimed, Self::OnHold)
+                | (Self::Closed, Self::Open)
+                | (Self::Open | Self::Claimed | Self::OnHold, Self::Closed)
+        )
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, sqlx::FromRow)]
 pub struct Ticket {
     pub id: i32,
     pub guild_id: i64,
@@ -13,4 +47,36 @@ pub struct Ticket {
     pub title: String,
     pub info: String,
     pub created_at: chrono::NaiveDateTime,
+    pub status: TicketStatus,
+    pub claimed_by: Option<i64>,
+    pub close_reason: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+pub struct TicketSearchHit {
+    #[sqlx(flatten)]
+    pub ticket: Ticket,
+    pub rank: f32,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct TicketHistoryEntry {
+    pub id: i32,
+    pub ticket_id: i32,
+    /// `0` for the initial entry of a ticket created without an author.
+    pub actor_id: i64,
+    pub from_status: Option<TicketStatus>,
+    pub to_status: TicketStatus,
+    pub reason: Option<String>,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, sqlx::FromRow)]
+pub struct TicketMessage {
+    pub id: i32,
+    pub ticket_id: i32,
+    pub author_id: i64,
+    pub content: String,
+    pub attachment_urls: Vec<String>,
+    pub created_at: chrono::NaiveDateTime,
 }
@@ -0,0 +1,298 @@
+use chrono::{DateTime, NaiveDateTime};
+use tonic::Status;
+
+pub mod proto {
+    tonic::include_proto!("pagination");
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 5;
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            SortDirection::Ascending => "ASC",
+            SortDirection::Descending => "DESC",
+        }
+    }
+
+    /// Operator selecting the rows that come after a cursor in this direction.
+    pub fn comparison(self) -> &'static str {
+        match self {
+            SortDirection::Ascending => ">",
+            SortDirection::Descending => "<",
+        }
+    }
+}
+
+impl From<proto::SortDirection> for SortDirection {
+    fn from(value: proto::SortDirection) -> Self {
+        match value {
+            proto::SortDirection::Ascending => Self::Ascending,
+            proto::SortDirection::Descending => Self::Descending,
+        }
+    }
+}
+
+pub fn sort_direction_from_proto(sort_direction: i32) -> Result<SortDirection, Status> {
+    match proto::SortDirection::try_from(sort_direction) {
+        Ok(sort_direction) => Ok(SortDirection::from(sort_direction)),
+        Err(_) => Err(Status::invalid_argument(format!(
+            "unknown sort direction {}",
+            sort_direction
+        ))),
+    }
+}
+
+/// Position of the last row of a page, ordered by `(created_at, id)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageToken {
+    pub created_at: NaiveDateTime,
+    pub id: i64,
+}
+
+impl PageToken {
+    /// Encodes the position together with the direction of the listing it was taken from, since
+    /// the same position resumes a different set of rows in the other direction.
+    pub fn encode(&self, sort_direction: SortDirection) -> String {
+        encode_hex(&format!(
+            "{}:{}:{}",
+            sort_direction.keyword(),
+            self.created_at.and_utc().timestamp_micros(),
+            self.id
+        ))
+    }
+
+    /// Decodes a token produced by [`PageToken::encode`] for a listing in `sort_direction`. An
+    /// empty token means the first page.
+    pub fn decode(token: &str, sort_direction: SortDirection) -> Result<Option<Self>, Status> {
+        if token.is_empty() {
+            return Ok(None);
+        }
+
+        let invalid = || Status::invalid_argument("invalid page token");
+
+        let decoded = decode_hex(token).ok_or_else(invalid)?;
+        let (keyword, position) = decoded.split_once(':').ok_or_else(invalid)?;
+        if keyword != sort_direction.keyword() {
+            return Err(Status::invalid_argument(
+                "page token belongs to a listing in the other sort direction",
+            ));
+        }
+
+        let (micros, id) = position.split_once(':').ok_or_else(invalid)?;
+        let micros = micros.parse::<i64>().map_err(|_| invalid())?;
+        let id = id.parse::<i64>().map_err(|_| invalid())?;
+        let created_at = DateTime::from_timestamp_micros(micros)
+            .ok_or_else(invalid)?
+            .naive_utc();
+
+        Ok(Some(Self { created_at, id }))
+    }
+}
+
+/// Position in a ranked result set, such as search results, which has no stable key to resume
+/// from.
+#[derive(Debug, Clone, Copy)]
+pub struct OffsetToken {
+    pub offset: i64,
+}
+
+impl OffsetToken {
+    pub fn encode(&self) -> String {
+        encode_hex(&format!("offset:{}", self.offset))
+    }
+
+    /// Decodes a token produced by [`OffsetToken::encode`]. An empty token means the first page.
+    pub fn decode(token: &str) -> Result<Self, Status> {
+        if token.is_empty() {
+            return Ok(Self { offset: 0 });
+        }
+
+        let invalid = || Status::invalid_argument("invalid page token");
+
+        let decoded = decode_hex(token).ok_or_else(invalid)?;
+        let offset = decoded
+            .strip_prefix("offset:")
+            .ok_or_else(invalid)?
+            .parse::<i64>()
+            .map_err(|_| invalid())?;
+
+        if offset < 0 {
+            return Err(invalid());
+        }
+
+        Ok(Self { offset })
+    }
+
+    /// Like [`next_page_token`], but the following page starts `page_size` rows past this one
+    /// rather than after the last row kept.
+    pub fn next_page_token<T>(&self, rows: &mut Vec<T>, page_size: u32) -> String {
+        if rows.len() <= page_size as usize {
+            return String::new();
+        }
+
+        rows.truncate(page_size as usize);
+
+        OffsetToken {
+            offset: self.offset + i64::from(page_size),
+        }
+        .encode()
+    }
+}
+
+fn encode_hex(value: &str) -> String {
+    value.bytes().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_hex(value: &str) -> Option<String> {
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return None;
+    }
+
+    let bytes = (0..value.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&value[index..index + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .ok()?;
+
+    String::from_utf8(bytes).ok()
+}
+
+/// Clamps a requested page size, treating zero as the default.
+pub fn page_size(requested: u32) -> u32 {
+    match requested {
+        0 => DEFAULT_PAGE_SIZE,
+        size => size.min(MAX_PAGE_SIZE),
+    }
+}
+
+/// Trims a result fetched with `page_size + 1` rows down to `page_size` and returns the token
+/// for the following page, or an empty string when there is none.
+pub fn next_page_token<T>(
+    rows: &mut Vec<T>,
+    page_size: u32,
+    sort_direction: SortDirection,
+    cursor: impl Fn(&T) -> PageToken,
+) -> String {
+    if rows.len() <= page_size as usize {
+        return String::new();
+    }
+
+    rows.truncate(page_size as usize);
+
+    match rows.last() {
+        Some(row) => cursor(row).encode(sort_direction),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(micros: i64, id: i64) -> PageToken {
+        PageToken {
+            created_at: DateTime::from_timestamp_micros(micros).unwrap().naive_utc(),
+            id,
+        }
+    }
+
+    #[test]
+    fn page_tokens_round_trip_through_hex() {
+        let page_token = token(1_710_000_000_123_456, 42);
+
+        let encoded = page_token.encode(SortDirection::Descending);
+
+        assert!(encoded.bytes().all(|byte| byte.is_ascii_hexdigit()));
+        assert_eq!(
+            PageToken::decode(&encoded, SortDirection::Descending).unwrap(),
+            Some(page_token)
+        );
+    }
+
+    #[test]
+    fn an_empty_page_token_is_the_first_page() {
+        assert_eq!(
+            PageToken::decode("", SortDirection::Ascending).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_page_tokens() {
+        for token in [
+            "zz",
+            "abc",
+            "ff",
+            &encode_hex("ASC"),
+            &encode_hex("ASC:12"),
+            &encode_hex("ASC:twelve:1"),
+            &encode_hex("ASC:12:one"),
+            &encode_hex(&format!("ASC:{}:1", i64::MAX)),
+        ] {
+            let status = PageToken::decode(token, SortDirection::Ascending).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "{}", token);
+        }
+    }
+
+    #[test]
+    fn rejects_page_tokens_from_the_other_sort_direction() {
+        let encoded = token(1_710_000_000_000_000, 7).encode(SortDirection::Ascending);
+
+        let status = PageToken::decode(&encoded, SortDirection::Descending).unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn rejects_unknown_sort_directions() {
+        assert_eq!(
+            sort_direction_from_proto(1).unwrap(),
+            SortDirection::Descending
+        );
+        assert_eq!(
+            sort_direction_from_proto(2).unwrap_err().code(),
+            tonic::Code::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn clamps_page_sizes() {
+        assert_eq!(page_size(0), DEFAULT_PAGE_SIZE);
+        assert_eq!(page_size(5), 5);
+        assert_eq!(page_size(1), 1);
+        assert_eq!(page_size(100), MAX_PAGE_SIZE);
+        assert_eq!(page_size(101), MAX_PAGE_SIZE);
+        assert_eq!(page_size(u32::MAX), MAX_PAGE_SIZE);
+    }
+
+    #[test]
+    fn next_page_token_points_at_the_last_row_kept() {
+        let mut rows = vec![1, 2, 3, 4];
+
+        let next = next_page_token(&mut rows, 3, SortDirection::Ascending, |id| token(0, *id));
+
+        assert_eq!(rows, vec![1, 2, 3]);
+        assert_eq!(
+            PageToken::decode(&next, SortDirection::Ascending).unwrap(),
+            Some(token(0, 3))
+        );
+    }
+
+    #[test]
+    fn next_page_token_is_empty_on_the_last_page() {
+        let mut rows = vec![1, 2, 3];
+
+        let next = next_page_token(&mut rows, 3, SortDirection::Ascending, |id| token(0, *id));
+
+        assert_eq!(rows, vec![1, 2, 3]);
+        assert_eq!(next, "");
+    }
+}
@@ -0,0 +1,106 @@
+use sqlx::PgExecutor;
+use tracing::debug;
+
+use crate::{models::staff::StaffAction, utils::sqlx_error_to_tonic_status};
+
+pub mod proto {
+    tonic::include_proto!("staff");
+}
+
+impl From<StaffAction> for proto::StaffAction {
+    fn from(value: StaffAction) -> Self {
+        match value {
+            StaffAction::Warn => Self::Warn,
+            StaffAction::Timeout => Self::Timeout,
+            StaffAction::Kick => Self::Kick,
+            StaffAction::Ban => Self::Ban,
+            StaffAction::Unban => Self::Unban,
+            StaffAction::Note => Self::Note,
+            StaffAction::ManageWarns => Self::ManageWarns,
+            StaffAction::ManageTickets => Self::ManageTickets,
+            StaffAction::DeleteTickets => Self::DeleteTickets,
+        }
+    }
+}
+
+impl From<proto::StaffAction> for StaffAction {
+    fn from(value: proto::StaffAction) -> Self {
+        match value {
+            proto::StaffAction::Warn => Self::Warn,
+            proto::StaffAction::Timeout => Self::Timeout,
+            proto::StaffAction::Kick => Self::Kick,
+            proto::StaffAction::Ban => Self::Ban,
+            proto::StaffAction::Unban => Self::Unban,
+            proto::StaffAction::Note => Self::Note,
+            proto::StaffAction::ManageWarns => Self::ManageWarns,
+            proto::StaffAction::ManageTickets => Self::ManageTickets,
+            proto::StaffAction::DeleteTickets => Self::DeleteTickets,
+        }
+    }
+}
+
+pub fn staff_action_from_proto(action: i32) -> Result<StaffAction, tonic::Status> {
+    match proto::StaffAction::try_from(action) {
+        Ok(action) => Ok(StaffAction::from(action)),
+        Err(_) => Err(tonic::Status::invalid_argument(format!(
+            "unknown staff action {}",
+            action
+        ))),
+    }
+}
+
+/// Whether any of `role_ids` is a staff role in the guild that grants `action`.
+pub async fn is_allowed<'e>(
+    executor: impl PgExecutor<'e>,
+    guild_id: i64,
+    role_ids: &[i64],
+    action: StaffAction,
+) -> Result<bool, sqlx::Error> {
+    let query = "SELECT EXISTS (SELECT 1 FROM permission WHERE guild_id = $1 AND role_id = ANY($2) AND action = $3)";
+    sqlx::query_scalar::<_, bool>(query)
+        .bind(guild_id)
+        .bind(role_ids)
+        .bind(action)
+        .fetch_one(executor)
+        .await
+}
+
+/// Enforces `action` when the request says which roles the acting staff member holds. Requests
+/// without staff context are trusted, as they were before permissions existed.
+///
+/// Call this inside the transaction that performs the action. The matching permission stays
+/// locked until that transaction ends, so a concurrent revocation waits for it instead of the
+/// action going through after the role lost the permission.
+pub async fn require<'e>(
+    executor: impl PgExecutor<'e>,
+    guild_id: i64,
+    staff: Option<&proto::StaffContext>,
+    action: StaffAction,
+) -> Result<(), tonic::Status> {
+    let Some(staff) = staff else {
+        return Ok(());
+    };
+
+    let query = "SELECT role_id FROM permission WHERE guild_id = $1 AND role_id = ANY($2) AND action = $3 LIMIT 1 FOR SHARE";
+    let result = sqlx::query_scalar::<_, i64>(query)
+        .bind(guild_id)
+        .bind(&staff.role_ids)
+        .bind(action)
+        .fetch_optional(executor)
+        .await;
+
+    match result {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => {
+            debug!(
+                "denied {:?} in guild {} to roles {:?}",
+                action, guild_id, staff.role_ids
+            );
+            Err(tonic::Status::permission_denied(format!(
+                "none of the staff member's roles may perform {:?}",
+                action
+            )))
+        }
+        Err(error) => Err(sqlx_error_to_tonic_status(&error)),
+    }
+}
@@ -0,0 +1,182 @@
+use proto::admin_service_server;
+use sqlx::PgPool;
+use tracing::{info, warn};
+
+use crate::{
+    auth::{self, ApiKeyCache, Scope},
+    models,
+    utils::sqlx_error_to_tonic_status,
+};
+
+pub mod proto {
+    tonic::include_proto!("admin");
+
+    pub(crate) const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("admin_descriptor");
+}
+
+impl From<models::admin::ApiKey> for proto::ApiKey {
+    fn from(value: models::admin::ApiKey) -> Self {
+        Self {
+            id: value.id,
+            name: value.name,
+            scopes: value.scopes,
+            guild_ids: value.guild_ids.unwrap_or_default(),
+            created_at: value.created_at.and_utc().timestamp(),
+            revoked_at: value
+                .revoked_at
+                .map(|revoked_at| revoked_at.and_utc().timestamp()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AdminService {
+    pool: PgPool,
+    api_keys: ApiKeyCache,
+}
+
+impl AdminService {
+    pub fn new(pool: PgPool, api_keys: ApiKeyCache) -> Self {
+        Self { pool, api_keys }
+    }
+
+    /// Reloads this replica's API keys so a change takes effect immediately. Other replicas pick it
+    /// up on their next periodic refresh.
+    async fn refresh_api_keys(&self) {
+        if let Err(error) = self.api_keys.refresh(&self.pool).await {
+            warn!("failed to refresh API keys: {}", error);
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl admin_service_server::AdminService for AdminService {
+    async fn create_api_key(
+        &self,
+        request: tonic::Request<proto::CreateApiKeyRequest>,
+    ) -> Result<tonic::Response<proto::CreatedApiKey>, tonic::Status> {
+        info!("handling `create_api_key`");
+
+        auth::authorize(&request, Scope::Admin, None)?;
+
+        let new_key = request.get_ref();
+
+        if new_key.name.trim().is_empty() {
+            return Err(tonic::Status::invalid_argument(
+                "API key name must not be empty",
+            ));
+        }
+
+        if new_key.scopes.is_empty() {
+            return Err(tonic::Status::invalid_argument(
+                "API key must have at least one scope",
+            ));
+        }
+
+        for scope in &new_key.scopes {
+            if Scope::parse(scope).is_none() {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "unknown scope `{}`",
+                    scope
+                )));
+            }
+        }
+
+        // An empty list means the key isn't limited to specific guilds.
+        let guild_ids = if new_key.guild_ids.is_empty() {
+            None
+        } else {
+            Some(&new_key.guild_ids)
+        };
+
+        let token = auth::generate_token();
+
+        let query = "INSERT INTO api_key (name, key_hash, scopes, guild_ids) VALUES ($1, $2, $3, $4) RETURNING *";
+        let result = sqlx::query_as::<_, models::admin::ApiKey>(query)
+            .bind(new_key.name.trim())
+            .bind(auth::hash_token(&token))
+            .bind(&new_key.scopes)
+            .bind(guild_ids)
+            .fetch_one(&self.pool)
+            .await;
+
+        let api_key = match result {
+            Ok(api_key) => api_key,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        info!(
+            "API key {} created by {}",
+            api_key.id,
+            auth::caller(&request)?
+        );
+
+        self.refresh_api_keys().await;
+
+        Ok(tonic::Response::new(proto::CreatedApiKey {
+            api_key: Some(proto::ApiKey::from(api_key)),
+            token,
+        }))
+    }
+
+    async fn list_api_keys(
+        &self,
+        request: tonic::Request<()>,
+    ) -> Result<tonic::Response<proto::ApiKeys>, tonic::Status> {
+        info!("handling `list_api_keys`");
+
+        auth::authorize(&request, Scope::Admin, None)?;
+
+        let query = "SELECT * FROM api_key ORDER BY id";
+        let result = sqlx::query_as::<_, models::admin::ApiKey>(query)
+            .fetch_all(&self.pool)
+            .await;
+
+        match result {
+            Ok(api_keys) => Ok(tonic::Response::new(proto::ApiKeys {
+                api_keys: api_keys.into_iter().map(proto::ApiKey::from).collect(),
+            })),
+            Err(error) => Err(sqlx_error_to_tonic_status(&error)),
+        }
+    }
+
+    async fn revoke_api_key(
+        &self,
+        request: tonic::Request<proto::ApiKeyRequest>,
+    ) -> Result<tonic::Response<proto::ApiKey>, tonic::Status> {
+        info!("handling `revoke_api_key`");
+
+        auth::authorize(&request, Scope::Admin, None)?;
+
+        let id = request.get_ref().id;
+
+        let query =
+            "UPDATE api_key SET revoked_at = NOW() WHERE id = $1 AND revoked_at IS NULL RETURNING *";
+        let result = sqlx::query_as::<_, models::admin::ApiKey>(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await;
+
+        let api_key = match result {
+            Ok(Some(api_key)) => api_key,
+            Ok(None) => {
+                return Err(tonic::Status::not_found(format!(
+                    "no active API key {}",
+                    id
+                )))
+            }
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        info!(
+            "API key {} revoked by {}",
+            api_key.id,
+            auth::caller(&request)?
+        );
+
+        self.refresh_api_keys().await;
+
+        Ok(tonic::Response::new(proto::ApiKey::from(api_key)))
+    }
+}
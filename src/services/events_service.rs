@@ -0,0 +1,133 @@
+use std::pin::Pin;
+
+use proto::events_service_server;
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream, StreamExt,
+};
+use tracing::{info, warn};
+
+use crate::{
+    auth::{self, Scope},
+    events::{Event, EventBus, EventKind, Operation},
+};
+
+pub mod proto {
+    tonic::include_proto!("events");
+
+    pub(crate) const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("events_descriptor");
+}
+
+impl From<EventKind> for proto::EventKind {
+    fn from(value: EventKind) -> Self {
+        match value {
+            EventKind::Infraction => Self::Infraction,
+            EventKind::InfractionExpired => Self::InfractionExpired,
+            EventKind::Ticket => Self::Ticket,
+            EventKind::LogsSettings => Self::LogsSettings,
+            EventKind::TicketsSettings => Self::TicketsSettings,
+            EventKind::AutomodSettings => Self::AutomodSettings,
+        }
+    }
+}
+
+impl From<proto::EventKind> for EventKind {
+    fn from(value: proto::EventKind) -> Self {
+        match value {
+            proto::EventKind::Infraction => Self::Infraction,
+            proto::EventKind::InfractionExpired => Self::InfractionExpired,
+            proto::EventKind::Ticket => Self::Ticket,
+            proto::EventKind::LogsSettings => Self::LogsSettings,
+            proto::EventKind::TicketsSettings => Self::TicketsSettings,
+            proto::EventKind::AutomodSettings => Self::AutomodSettings,
+        }
+    }
+}
+
+impl From<Operation> for proto::Operation {
+    fn from(value: Operation) -> Self {
+        match value {
+            Operation::Insert => Self::Insert,
+            Operation::Update => Self::Update,
+            Operation::Delete => Self::Delete,
+        }
+    }
+}
+
+impl From<Event> for proto::Event {
+    fn from(value: Event) -> Self {
+        Self {
+            kind: proto::EventKind::from(value.kind).into(),
+            operation: proto::Operation::from(value.operation).into(),
+            guild_id: value.guild_id,
+            entity_id: value.entity_id,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EventsService {
+    events: EventBus,
+}
+
+impl EventsService {
+    pub fn new(events: EventBus) -> Self {
+        Self { events }
+    }
+}
+
+#[tonic::async_trait]
+impl events_service_server::EventsService for EventsService {
+    type SubscribeStream = Pin<Box<dyn Stream<Item = Result<proto::Event, tonic::Status>> + Send>>;
+
+    async fn subscribe(
+        &self,
+        request: tonic::Request<proto::SubscribeRequest>,
+    ) -> Result<tonic::Response<Self::SubscribeStream>, tonic::Status> {
+        info!("handling `subscribe`");
+
+        // Subscribing without a guild filter receives events from every guild.
+        let guild_ids = &request.get_ref().guild_ids;
+        if guild_ids.is_empty() {
+            auth::authorize(&request, Scope::EventsRead, None)?;
+        }
+        for &guild_id in guild_ids {
+            auth::authorize(&request, Scope::EventsRead, Some(guild_id))?;
+        }
+
+        let subscribe_request = request.into_inner();
+
+        let mut kinds = Vec::with_capacity(subscribe_request.kinds.len());
+        for &kind in &subscribe_request.kinds {
+            match proto::EventKind::try_from(kind) {
+                Ok(kind) => kinds.push(EventKind::from(kind)),
+                Err(_) => {
+                    return Err(tonic::Status::invalid_argument(format!(
+                        "unknown event kind {}",
+                        kind
+                    )))
+                }
+            }
+        }
+
+        let guild_ids = subscribe_request.guild_ids;
+
+        let stream =
+            BroadcastStream::new(self.events.subscribe()).filter_map(move |event| match event {
+                Ok(event)
+                    if (guild_ids.is_empty() || guild_ids.contains(&event.guild_id))
+                        && (kinds.is_empty() || kinds.contains(&event.kind)) =>
+                {
+                    Some(Ok(proto::Event::from(event)))
+                }
+                Ok(_) => None,
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    warn!("event subscriber lagged, skipped {} events", skipped);
+                    None
+                }
+            });
+
+        Ok(tonic::Response::new(Box::pin(stream)))
+    }
+}
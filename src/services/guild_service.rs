@@ -1,8 +1,19 @@
+use std::{collections::HashMap, pin::Pin, sync::Arc, time::Duration};
+
 use proto::guild_service_server;
-use sqlx::PgPool;
-use tracing::info;
+use sqlx::{postgres::PgRow, FromRow, PgConnection, PgPool, Postgres, Transaction};
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tracing::{debug, error, info};
 
-use crate::utils::sqlx_error_to_tonic_status;
+use crate::{
+    archive::{self, Record},
+    auth::{self, Caller, Scope},
+    models::{self, logs::LogIgnoreKind},
+    permissions::{self, staff_action_from_proto},
+    services::{logs_service, moderation_service, tickets_service},
+    utils::{ensure_guild_active, lock_active_guild, sqlx_error_to_tonic_status},
+};
 
 pub mod proto {
     tonic::include_proto!("guild");
@@ -11,19 +22,676 @@ pub mod proto {
         tonic::include_file_descriptor_set!("guild_descriptor");
 }
 
+/// Exports hold a pooled connection for as long as the client takes to read them, so only a few
+/// may run at once to leave connections for every other RPC.
+const MAX_CONCURRENT_EXPORTS: usize = 2;
+
+/// An export is abandoned when the client hasn't accepted the next chunk within this long.
+const EXPORT_SEND_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An import holds a pooled connection from its header until its last record is applied, so
+/// imports are limited like exports.
+const MAX_CONCURRENT_IMPORTS: usize = 1;
+
+/// An import is abandoned when the client hasn't sent the next chunk within this long.
+const IMPORT_RECEIVE_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Debug)]
 pub struct GuildService {
     pool: PgPool,
+    exports: Arc<Semaphore>,
+    imports: Semaphore,
 }
 
 impl GuildService {
     pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+        Self {
+            pool,
+            exports: Arc::new(Semaphore::new(MAX_CONCURRENT_EXPORTS)),
+            imports: Semaphore::new(MAX_CONCURRENT_IMPORTS),
+        }
+    }
+}
+
+/// Every staff role of a guild with the actions it grants.
+const STAFF_ROLES_QUERY: &str = "SELECT staff_role.role_id, \
+    COALESCE(array_agg(permission.action ORDER BY permission.action) FILTER (WHERE permission.action IS NOT NULL), '{}') AS actions \
+    FROM staff_role LEFT JOIN permission USING (guild_id, role_id) \
+    WHERE staff_role.guild_id = $1 GROUP BY staff_role.role_id ORDER BY staff_role.role_id";
+
+type ExportSender = mpsc::Sender<Result<proto::ExportChunk, tonic::Status>>;
+
+/// Buffers archive records and sends them to the client once a chunk's worth has built up.
+struct ExportWriter {
+    sender: ExportSender,
+    buffer: Vec<u8>,
+}
+
+impl ExportWriter {
+    fn new(sender: ExportSender) -> Self {
+        Self {
+            sender,
+            buffer: Vec::with_capacity(archive::CHUNK_SIZE),
+        }
+    }
+
+    async fn write(&mut self, record: &Record) -> Result<(), tonic::Status> {
+        if let Err(error) = archive::write_record(&mut self.buffer, record) {
+            return Err(tonic::Status::internal(format!(
+                "failed to serialize record: {}",
+                error
+            )));
+        }
+
+        if self.buffer.len() >= archive::CHUNK_SIZE {
+            self.flush().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn flush(&mut self) -> Result<(), tonic::Status> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+
+        let data = std::mem::replace(&mut self.buffer, Vec::with_capacity(archive::CHUNK_SIZE));
+
+        let send = self.sender.send(Ok(proto::ExportChunk { data }));
+
+        match tokio::time::timeout(EXPORT_SEND_TIMEOUT, send).await {
+            Ok(Ok(_)) => Ok(()),
+            Ok(Err(_)) => Err(tonic::Status::cancelled("export stream was closed")),
+            Err(_) => Err(tonic::Status::deadline_exceeded(
+                "client stopped reading the export",
+            )),
+        }
+    }
+}
+
+/// Writes one record for every row returned by `query`, which is bound to the guild's ID.
+async fn export_rows<T>(
+    transaction: &mut Transaction<'static, Postgres>,
+    writer: &mut ExportWriter,
+    query: &str,
+    guild_id: i64,
+    record: impl Fn(T) -> Record + Send,
+) -> Result<(), tonic::Status>
+where
+    T: for<'r> FromRow<'r, PgRow> + Send + Unpin,
+{
+    let mut rows = sqlx::query_as::<_, T>(query)
+        .bind(guild_id)
+        .fetch(&mut **transaction);
+
+    while let Some(row) = rows.next().await {
+        match row {
+            Ok(row) => writer.write(&record(row)).await?,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+    }
+
+    Ok(())
+}
+
+async fn write_archive(
+    mut transaction: Transaction<'static, Postgres>,
+    writer: &mut ExportWriter,
+    guild_id: i64,
+) -> Result<(), tonic::Status> {
+    writer
+        .write(&Record::Header {
+            schema_version: archive::SCHEMA_VERSION,
+            guild_id,
+            exported_at: chrono::Utc::now().naive_utc(),
+        })
+        .await?;
+
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT * FROM logs_settings WHERE guild_id = $1",
+        guild_id,
+        Record::LogsSettings,
+    )
+    .await?;
+
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT * FROM logs_route WHERE guild_id = $1 ORDER BY category",
+        guild_id,
+        Record::LogsRoute,
+    )
+    .await?;
+
+    for kind in [
+        LogIgnoreKind::Channel,
+        LogIgnoreKind::User,
+        LogIgnoreKind::Role,
+    ] {
+        let query = format!(
+            "SELECT {} FROM {} WHERE guild_id = $1 ORDER BY {}",
+            kind.column(),
+            kind.table(),
+            kind.column()
+        );
+        export_rows(
+            &mut transaction,
+            writer,
+            &query,
+            guild_id,
+            |(id,): (i64,)| Record::LogIgnore { kind, id },
+        )
+        .await?;
+    }
+
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT * FROM tickets_settings WHERE guild_id = $1",
+        guild_id,
+        Record::TicketsSettings,
+    )
+    .await?;
+
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT * FROM automod_settings WHERE guild_id = $1",
+        guild_id,
+        Record::AutomodSettings,
+    )
+    .await?;
+
+    export_rows(
+        &mut transaction,
+        writer,
+        STAFF_ROLES_QUERY,
+        guild_id,
+        Record::StaffRole,
+    )
+    .await?;
+
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT * FROM ticket WHERE guild_id = $1 ORDER BY id",
+        guild_id,
+        Record::Ticket,
+    )
+    .await?;
+
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT ticket_message.* FROM ticket_message \
+            JOIN ticket ON ticket.id = ticket_message.ticket_id \
+            WHERE ticket.guild_id = $1 ORDER BY ticket_message.id",
+        guild_id,
+        Record::TicketMessage,
+    )
+    .await?;
+
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT ticket_history.* FROM ticket_history \
+            JOIN ticket ON ticket.id = ticket_history.ticket_id \
+            WHERE ticket.guild_id = $1 ORDER BY ticket_history.id",
+        guild_id,
+        Record::TicketHistory,
+    )
+    .await?;
+
+    // Automod actions reference the warn that triggered them, which always has a lower ID.
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT * FROM infraction WHERE guild_id = $1 ORDER BY id",
+        guild_id,
+        Record::Infraction,
+    )
+    .await?;
+
+    export_rows(
+        &mut transaction,
+        writer,
+        "SELECT * FROM audit_event WHERE guild_id = $1 ORDER BY id",
+        guild_id,
+        Record::AuditEvent,
+    )
+    .await?;
+
+    match transaction.commit().await {
+        Ok(_) => {}
+        Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+    }
+
+    writer.flush().await
+}
+
+/// Applies archive records inside one transaction. Tickets and infractions are given fresh IDs
+/// on insert, so references to them are remapped as the records that own them are applied.
+///
+/// The transaction only begins once the header has been checked and authorized, so a stream
+/// that never sends a valid header doesn't hold a connection.
+struct ArchiveImporter {
+    caller: Caller,
+    pool: PgPool,
+    transaction: Option<Transaction<'static, Postgres>>,
+    guild_id: Option<i64>,
+    line_number: usize,
+    ticket_ids: HashMap<i32, i32>,
+    infraction_ids: HashMap<i32, i32>,
+    summary: proto::ImportSummary,
+}
+
+impl ArchiveImporter {
+    fn new(caller: Caller, pool: PgPool) -> Self {
+        Self {
+            caller,
+            pool,
+            transaction: None,
+            guild_id: None,
+            line_number: 0,
+            ticket_ids: HashMap::new(),
+            infraction_ids: HashMap::new(),
+            summary: proto::ImportSummary::default(),
+        }
+    }
+
+    fn invalid(&self, message: impl std::fmt::Display) -> tonic::Status {
+        tonic::Status::invalid_argument(format!("line {}: {}", self.line_number, message))
+    }
+
+    /// The connection of the import's transaction, which begins with the header.
+    fn transaction(&mut self) -> Result<&mut PgConnection, tonic::Status> {
+        let line_number = self.line_number;
+
+        match self.transaction.as_mut() {
+            Some(transaction) => Ok(&mut **transaction),
+            None => Err(tonic::Status::invalid_argument(format!(
+                "line {}: archive must start with a header",
+                line_number
+            ))),
+        }
+    }
+
+    /// Ensures a record belongs to the guild named in the header.
+    fn check_guild(&self, guild_id: i64) -> Result<(), tonic::Status> {
+        match self.guild_id {
+            Some(expected) if expected == guild_id => Ok(()),
+            Some(expected) => Err(self.invalid(format!(
+                "record belongs to guild {}, expected {}",
+                guild_id, expected
+            ))),
+            None => Err(self.invalid("archive must start with a header")),
+        }
+    }
+
+    /// Rejects the next line once `length` bytes of it have arrived without a newline, if that is
+    /// already more than a record may take up.
+    fn check_line_length(&self, length: usize) -> Result<(), tonic::Status> {
+        if length > archive::MAX_LINE_SIZE {
+            return Err(tonic::Status::invalid_argument(format!(
+                "line {} is longer than {} bytes",
+                self.line_number + 1,
+                archive::MAX_LINE_SIZE
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn ticket_id(&self, id: i32) -> Result<i32, tonic::Status> {
+        match self.ticket_ids.get(&id) {
+            Some(id) => Ok(*id),
+            None => Err(self.invalid(format!("record references unknown ticket {}", id))),
+        }
+    }
+
+    fn infraction_id(&self, id: i32) -> Result<i32, tonic::Status> {
+        match self.infraction_ids.get(&id) {
+            Some(id) => Ok(*id),
+            None => Err(self.invalid(format!("record references unknown infraction {}", id))),
+        }
+    }
+
+    async fn apply_line(&mut self, line: &[u8]) -> Result<(), tonic::Status> {
+        self.line_number += 1;
+
+        if line.iter().all(u8::is_ascii_whitespace) {
+            return Ok(());
+        }
+
+        match archive::read_record(line) {
+            Ok(record) => self.apply(record).await,
+            Err(error) => Err(self.invalid(error)),
+        }
+    }
+
+    async fn apply(&mut self, record: Record) -> Result<(), tonic::Status> {
+        let result = match record {
+            Record::Header {
+                schema_version,
+                guild_id,
+                ..
+            } => return self.start(schema_version, guild_id).await,
+            Record::LogsSettings(settings) => {
+                self.check_guild(settings.guild_id)?;
+                self.summary.settings += 1;
+
+                let query = "INSERT INTO logs_settings VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id) DO UPDATE SET enabled = $2, channel_id = $3, retention_days = $4";
+                sqlx::query(query)
+                    .bind(settings.guild_id)
+                    .bind(settings.enabled)
+                    .bind(settings.channel_id)
+                    .bind(settings.retention_days)
+                    .execute(self.transaction()?)
+                    .await
+            }
+            Record::LogsRoute(route) => {
+                self.check_guild(route.guild_id)?;
+                self.summary.log_routes += 1;
+
+                let query = "INSERT INTO logs_route VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id, category) DO UPDATE SET enabled = $3, channel_id = $4";
+                sqlx::query(query)
+                    .bind(route.guild_id)
+                    .bind(route.category)
+                    .bind(route.enabled)
+                    .bind(route.channel_id)
+                    .execute(self.transaction()?)
+                    .await
+            }
+            Record::LogIgnore { kind, id } => {
+                let Some(guild_id) = self.guild_id else {
+                    return Err(self.invalid("archive must start with a header"));
+                };
+                self.summary.log_ignores += 1;
+
+                let query = format!(
+                    "INSERT INTO {} (guild_id, {}) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+                    kind.table(),
+                    kind.column()
+                );
+                sqlx::query(&query)
+                    .bind(guild_id)
+                    .bind(id)
+                    .execute(self.transaction()?)
+                    .await
+            }
+            Record::TicketsSettings(settings) => {
+                self.check_guild(settings.guild_id)?;
+                self.summary.settings += 1;
+
+                let query = "INSERT INTO tickets_settings VALUES ($1, $2, $3) ON CONFLICT (guild_id) DO UPDATE SET enabled = $2, channel_id = $3";
+                sqlx::query(query)
+                    .bind(settings.guild_id)
+                    .bind(settings.enabled)
+                    .bind(settings.channel_id)
+                    .execute(self.transaction()?)
+                    .await
+            }
+            Record::AutomodSettings(settings) => {
+                self.check_guild(settings.guild_id)?;
+                self.summary.settings += 1;
+
+                let query = "INSERT INTO automod_settings VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (guild_id) \
+                    DO UPDATE SET autoban_enabled = $2, autoban_threshold = $3, autokick_enabled = $4, autokick_threshold = $5, warn_expiry_days = $6";
+                sqlx::query(query)
+                    .bind(settings.guild_id)
+                    .bind(settings.autoban_enabled)
+                    .bind(settings.autoban_threshold)
+                    .bind(settings.autokick_enabled)
+                    .bind(settings.autokick_threshold)
+                    .bind(settings.warn_expiry_days)
+                    .execute(self.transaction()?)
+                    .await
+            }
+            Record::StaffRole(role) => {
+                let Some(guild_id) = self.guild_id else {
+                    return Err(self.invalid("archive must start with a header"));
+                };
+                self.summary.staff_roles += 1;
+
+                let query = "INSERT INTO staff_role VALUES ($1, $2) ON CONFLICT DO NOTHING";
+                let result = sqlx::query(query)
+                    .bind(guild_id)
+                    .bind(role.role_id)
+                    .execute(self.transaction()?)
+                    .await;
+
+                match result {
+                    Ok(_) => {}
+                    Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+                }
+
+                let query = "INSERT INTO permission SELECT $1, $2, action FROM unnest($3::staff_action[]) AS action ON CONFLICT DO NOTHING";
+                sqlx::query(query)
+                    .bind(guild_id)
+                    .bind(role.role_id)
+                    .bind(&role.actions)
+                    .execute(self.transaction()?)
+                    .await
+            }
+            Record::Ticket(ticket) => {
+                self.check_guild(ticket.guild_id)?;
+                self.summary.tickets += 1;
+
+                let query = "INSERT INTO ticket (guild_id, author_id, title, info, created_at, status, claimed_by, close_reason) \
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING id";
+                let result = sqlx::query_scalar::<_, i32>(query)
+                    .bind(ticket.guild_id)
+                    .bind(ticket.author_id)
+                    .bind(&ticket.title)
+                    .bind(&ticket.info)
+                    .bind(ticket.created_at)
+                    .bind(ticket.status)
+                    .bind(ticket.claimed_by)
+                    .bind(&ticket.close_reason)
+                    .fetch_one(self.transaction()?)
+                    .await;
+
+                match result {
+                    Ok(id) => {
+                        self.ticket_ids.insert(ticket.id, id);
+                        return Ok(());
+                    }
+                    Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+                }
+            }
+            Record::TicketMessage(message) => {
+                let ticket_id = self.ticket_id(message.ticket_id)?;
+                self.summary.ticket_messages += 1;
+
+                let query = "INSERT INTO ticket_message (ticket_id, author_id, content, attachment_urls, created_at) VALUES ($1, $2, $3, $4, $5)";
+                sqlx::query(query)
+                    .bind(ticket_id)
+                    .bind(message.author_id)
+                    .bind(&message.content)
+                    .bind(&message.attachment_urls)
+                    .bind(message.created_at)
+                    .execute(self.transaction()?)
+                    .await
+            }
+            Record::TicketHistory(entry) => {
+                let ticket_id = self.ticket_id(entry.ticket_id)?;
+                self.summary.ticket_history += 1;
+
+                let query = "INSERT INTO ticket_history (ticket_id, actor_id, from_status, to_status, reason, created_at) VALUES ($1, $2, $3, $4, $5, $6)";
+                sqlx::query(query)
+                    .bind(ticket_id)
+                    .bind(entry.actor_id)
+                    .bind(entry.from_status)
+                    .bind(entry.to_status)
+                    .bind(&entry.reason)
+                    .bind(entry.created_at)
+                    .execute(self.transaction()?)
+                    .await
+            }
+            Record::Infraction(infraction) => {
+                self.check_guild(infraction.guild_id)?;
+                let triggered_by = match infraction.triggered_by {
+                    Some(id) => Some(self.infraction_id(id)?),
+                    None => None,
+                };
+                self.summary.infractions += 1;
+
+                // Case numbers are kept, and the trigger moves the guild's counter past them.
+                let query = "INSERT INTO infraction (guild_id, case_number, infraction_type, staff_member_id, target_user_id, reason, expires_at, triggered_by, created_at, lifted_at) \
+                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING id";
+                let result = sqlx::query_scalar::<_, i32>(query)
+                    .bind(infraction.guild_id)
+                    .bind(infraction.case_number)
+                    .bind(infraction.infraction_type)
+                    .bind(infraction.staff_member_id)
+                    .bind(infraction.target_user_id)
+                    .bind(&infraction.reason)
+                    .bind(infraction.expires_at)
+                    .bind(triggered_by)
+                    .bind(infraction.created_at)
+                    .bind(infraction.lifted_at)
+                    .fetch_one(self.transaction()?)
+                    .await;
+
+                match result {
+                    Ok(id) => {
+                        self.infraction_ids.insert(infraction.id, id);
+                        return Ok(());
+                    }
+                    Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+                }
+            }
+            Record::AuditEvent(event) => {
+                self.check_guild(event.guild_id)?;
+                self.summary.audit_events += 1;
+
+                let query = "INSERT INTO audit_event (guild_id, event_type, user_id, channel_id, payload, created_at) VALUES ($1, $2, $3, $4, $5, $6)";
+                sqlx::query(query)
+                    .bind(event.guild_id)
+                    .bind(event.event_type)
+                    .bind(event.user_id)
+                    .bind(event.channel_id)
+                    .bind(&event.payload)
+                    .bind(event.created_at)
+                    .execute(self.transaction()?)
+                    .await
+            }
+        };
+
+        match result {
+            Ok(_) => Ok(()),
+            Err(error) => Err(sqlx_error_to_tonic_status(&error)),
+        }
+    }
+
+    /// Handles the header: checks the schema version and prepares the target guild, which must not
+    /// already hold tickets, infractions or audit events that the archive would duplicate.
+    async fn start(&mut self, schema_version: u32, guild_id: i64) -> Result<(), tonic::Status> {
+        if self.guild_id.is_some() {
+            return Err(self.invalid("archive contains more than one header"));
+        }
+
+        if schema_version != archive::SCHEMA_VERSION {
+            return Err(self.invalid(format!(
+                "unsupported schema version {}, expected {}",
+                schema_version,
+                archive::SCHEMA_VERSION
+            )));
+        }
+
+        self.caller.authorize(Scope::GuildWrite, Some(guild_id))?;
+
+        let transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        self.transaction = Some(transaction);
+        self.guild_id = Some(guild_id);
+        self.summary.guild_id = guild_id;
+
+        let query =
+            "INSERT INTO guild VALUES ($1) ON CONFLICT (guild_id) DO UPDATE SET deleted_at = NULL";
+        let result = sqlx::query(query)
+            .bind(guild_id)
+            .execute(self.transaction()?)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        let query = "SELECT EXISTS (SELECT 1 FROM ticket WHERE guild_id = $1) \
+            OR EXISTS (SELECT 1 FROM infraction WHERE guild_id = $1) \
+            OR EXISTS (SELECT 1 FROM audit_event WHERE guild_id = $1)";
+        let result = sqlx::query_scalar::<_, bool>(query)
+            .bind(guild_id)
+            .fetch_one(self.transaction()?)
+            .await;
+
+        match result {
+            Ok(false) => {}
+            Ok(true) => {
+                return Err(tonic::Status::failed_precondition(format!(
+                    "guild {} already has tickets, infractions or audit events",
+                    guild_id
+                )))
+            }
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        // Settings missing from the archive fall back to defaults, as for a newly created guild.
+        for table in ["logs_settings", "tickets_settings", "automod_settings"] {
+            let query = format!(
+                "INSERT INTO {} (guild_id) VALUES ($1) ON CONFLICT DO NOTHING",
+                table
+            );
+            let result = sqlx::query(&query)
+                .bind(guild_id)
+                .execute(self.transaction()?)
+                .await;
+
+            match result {
+                Ok(_) => {}
+                Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Commits the import, or rolls it back in a dry run, and returns what was applied.
+    async fn finish(self, dry_run: bool) -> Result<proto::ImportSummary, tonic::Status> {
+        let Some(transaction) = self.transaction else {
+            return Err(tonic::Status::invalid_argument("archive is empty"));
+        };
+
+        let result = if dry_run {
+            transaction.rollback().await
+        } else {
+            transaction.commit().await
+        };
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(proto::ImportSummary {
+            dry_run,
+            ..self.summary
+        })
     }
 }
 
 #[tonic::async_trait]
 impl guild_service_server::GuildService for GuildService {
+    type ExportGuildStream =
+        Pin<Box<dyn Stream<Item = Result<proto::ExportChunk, tonic::Status>> + Send>>;
+
     async fn create_guild(
         &self,
         request: tonic::Request<proto::Guild>,
@@ -32,14 +700,47 @@ impl guild_service_server::GuildService for GuildService {
 
         let guild_id = request.get_ref().guild_id;
 
-        let query = "INSERT INTO guild VALUES ($1) ON CONFLICT DO NOTHING";
-        let result = sqlx::query(query).bind(guild_id).execute(&self.pool).await;
+        auth::authorize(&request, Scope::GuildWrite, Some(guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        // Recreating a soft-deleted guild, e.g. when the bot rejoins it, restores its data.
+        let query =
+            "INSERT INTO guild VALUES ($1) ON CONFLICT (guild_id) DO UPDATE SET deleted_at = NULL";
+        let result = sqlx::query(query)
+            .bind(guild_id)
+            .execute(&mut *transaction)
+            .await;
 
         match result {
             Ok(_) => {}
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
         }
 
+        for table in ["logs_settings", "tickets_settings", "automod_settings"] {
+            let query = format!(
+                "INSERT INTO {} (guild_id) VALUES ($1) ON CONFLICT DO NOTHING",
+                table
+            );
+            let result = sqlx::query(&query)
+                .bind(guild_id)
+                .execute(&mut *transaction)
+                .await;
+
+            match result {
+                Ok(_) => {}
+                Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+            }
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
         Ok(tonic::Response::new(()))
     }
 
@@ -51,7 +752,10 @@ impl guild_service_server::GuildService for GuildService {
 
         let guild_id = request.get_ref().guild_id;
 
-        let query = "DELETE FROM guild WHERE guild_id = $1";
+        auth::authorize(&request, Scope::GuildWrite, Some(guild_id))?;
+
+        let query =
+            "UPDATE guild SET deleted_at = NOW() WHERE guild_id = $1 AND deleted_at IS NULL";
         let result = sqlx::query(query).bind(guild_id).execute(&self.pool).await;
 
         match result {
@@ -61,4 +765,501 @@ impl guild_service_server::GuildService for GuildService {
 
         Ok(tonic::Response::new(()))
     }
+
+    async fn restore_guild(
+        &self,
+        request: tonic::Request<proto::Guild>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `restore_guild`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::GuildWrite, Some(guild_id))?;
+
+        let query =
+            "UPDATE guild SET deleted_at = NULL WHERE guild_id = $1 AND deleted_at IS NOT NULL";
+        let result = sqlx::query(query).bind(guild_id).execute(&self.pool).await;
+
+        match result {
+            Ok(result) if result.rows_affected() == 0 => {
+                return Err(tonic::Status::not_found(format!(
+                    "no deleted guild {} awaiting purge",
+                    guild_id
+                )))
+            }
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn get_guild_config(
+        &self,
+        request: tonic::Request<proto::Guild>,
+    ) -> Result<tonic::Response<proto::GuildConfig>, tonic::Status> {
+        info!("handling `get_guild_config`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::GuildRead, Some(guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        // Every read below sees the same snapshot, even if settings change between them.
+        let query = "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY";
+        let result = sqlx::query(query).execute(&mut *transaction).await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        let query = "SELECT guild_id FROM guild WHERE guild_id = $1 AND deleted_at IS NULL";
+        let result = sqlx::query(query)
+            .bind(guild_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        let query = "SELECT * FROM logs_settings WHERE guild_id = $1";
+        let result = sqlx::query_as::<_, models::logs::LogsSettings>(query)
+            .bind(guild_id)
+            .fetch_optional(&mut *transaction)
+            .await;
+
+        let logs_settings = match result {
+            Ok(settings) => {
+                settings.unwrap_or_else(|| models::logs::LogsSettings::default_for(guild_id))
+            }
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let query = "SELECT * FROM tickets_settings WHERE guild_id = $1";
+        let result = sqlx::query_as::<_, models::tickets::TicketsSettings>(query)
+            .bind(guild_id)
+            .fetch_optional(&mut *transaction)
+            .await;
+
+        let tickets_settings = match result {
+            Ok(settings) => {
+                settings.unwrap_or_else(|| models::tickets::TicketsSettings::default_for(guild_id))
+            }
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let query = "SELECT * FROM automod_settings WHERE guild_id = $1";
+        let result = sqlx::query_as::<_, models::moderation::AutomodSettings>(query)
+            .bind(guild_id)
+            .fetch_optional(&mut *transaction)
+            .await;
+
+        let automod_settings = match result {
+            Ok(settings) => settings
+                .unwrap_or_else(|| models::moderation::AutomodSettings::default_for(guild_id)),
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(proto::GuildConfig {
+            guild_id,
+            logs_settings: Some(logs_service::proto::LogsSettings::from(logs_settings)),
+            tickets_settings: Some(tickets_service::proto::TicketsSettings::from(
+                tickets_settings,
+            )),
+            automod_settings: Some(moderation_service::proto::AutomodSettings::from(
+                automod_settings,
+            )),
+        }))
+    }
+
+    async fn export_guild(
+        &self,
+        request: tonic::Request<proto::Guild>,
+    ) -> Result<tonic::Response<Self::ExportGuildStream>, tonic::Status> {
+        info!("handling `export_guild`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::GuildRead, Some(guild_id))?;
+
+        let permit = match self.exports.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                return Err(tonic::Status::resource_exhausted(
+                    "too many exports in progress, try again later",
+                ))
+            }
+        };
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        // The archive is read from one snapshot so tickets, messages and infractions line up.
+        let query = "SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY";
+        let result = sqlx::query(query).execute(&mut *transaction).await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        // Guilds awaiting purge can still be exported.
+        let query = "SELECT guild_id FROM guild WHERE guild_id = $1";
+        let result = sqlx::query(query)
+            .bind(guild_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        let (sender, receiver) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            // A slot is reserved up front so a failure can always be reported, even to a client
+            // that has stopped reading, rather than ending the stream as if the archive were whole.
+            let error_permit = match sender.clone().reserve_owned().await {
+                Ok(error_permit) => error_permit,
+                Err(_) => return,
+            };
+
+            let mut writer = ExportWriter::new(sender);
+
+            if let Err(status) = write_archive(transaction, &mut writer, guild_id).await {
+                error!("failed to export guild {}: {}", guild_id, status.message());
+                error_permit.send(Err(status));
+            }
+
+            drop(permit);
+        });
+
+        Ok(tonic::Response::new(Box::pin(ReceiverStream::new(
+            receiver,
+        ))))
+    }
+
+    async fn import_guild(
+        &self,
+        request: tonic::Request<tonic::Streaming<proto::ImportChunk>>,
+    ) -> Result<tonic::Response<proto::ImportSummary>, tonic::Status> {
+        info!("handling `import_guild`");
+
+        let _permit = match self.imports.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                return Err(tonic::Status::resource_exhausted(
+                    "too many imports in progress, try again later",
+                ))
+            }
+        };
+
+        // The target guild is only known once the header arrives, so it is authorized then.
+        let caller = auth::caller(&request)?.clone();
+        let mut chunks = request.into_inner();
+
+        let mut importer = ArchiveImporter::new(caller, self.pool.clone());
+        let mut dry_run = None;
+        let mut buffer = Vec::new();
+        let mut received = 0;
+
+        // Records are applied as soon as their line is complete, so only the current line is ever
+        // held in memory.
+        loop {
+            let chunk = match tokio::time::timeout(IMPORT_RECEIVE_TIMEOUT, chunks.message()).await {
+                Ok(Ok(Some(chunk))) => chunk,
+                Ok(Ok(None)) => break,
+                Ok(Err(status)) => return Err(status),
+                Err(_) => {
+                    return Err(tonic::Status::deadline_exceeded(
+                        "client stopped sending the archive",
+                    ))
+                }
+            };
+
+            dry_run.get_or_insert(chunk.dry_run);
+
+            received += chunk.data.len();
+            if received > archive::MAX_ARCHIVE_SIZE {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "archive is larger than {} bytes",
+                    archive::MAX_ARCHIVE_SIZE
+                )));
+            }
+
+            // Bytes already in the buffer are the start of a line, so they hold no newline.
+            let mut search_from = buffer.len();
+            let mut start = 0;
+            buffer.extend_from_slice(&chunk.data);
+
+            while let Some(offset) = buffer[search_from..].iter().position(|byte| *byte == b'\n') {
+                let end = search_from + offset;
+                importer.apply_line(&buffer[start..end]).await?;
+                start = end + 1;
+                search_from = start;
+            }
+
+            buffer.drain(..start);
+            importer.check_line_length(buffer.len())?;
+        }
+
+        if !buffer.is_empty() {
+            importer.apply_line(&buffer).await?;
+        }
+
+        let dry_run = dry_run.unwrap_or(false);
+        let summary = importer.finish(dry_run).await?;
+
+        info!(
+            "imported guild {} ({} tickets, {} infractions, {} audit events){}",
+            summary.guild_id,
+            summary.tickets,
+            summary.infractions,
+            summary.audit_events,
+            if dry_run { " as a dry run" } else { "" }
+        );
+
+        Ok(tonic::Response::new(summary))
+    }
+
+    async fn add_staff_role(
+        &self,
+        request: tonic::Request<proto::StaffRole>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `add_staff_role`");
+
+        let staff_role = request.get_ref();
+
+        auth::authorize(&request, Scope::GuildWrite, Some(staff_role.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, staff_role.guild_id).await?;
+
+        let query = "INSERT INTO staff_role VALUES ($1, $2) ON CONFLICT DO NOTHING";
+        let result = sqlx::query(query)
+            .bind(staff_role.guild_id)
+            .bind(staff_role.role_id)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn remove_staff_role(
+        &self,
+        request: tonic::Request<proto::StaffRole>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `remove_staff_role`");
+
+        let staff_role = request.get_ref();
+
+        auth::authorize(&request, Scope::GuildWrite, Some(staff_role.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, staff_role.guild_id).await?;
+
+        let query = "DELETE FROM staff_role WHERE guild_id = $1 AND role_id = $2";
+        let result = sqlx::query(query)
+            .bind(staff_role.guild_id)
+            .bind(staff_role.role_id)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(result) if result.rows_affected() == 0 => {
+                return Err(tonic::Status::not_found(format!(
+                    "role {} is not a staff role",
+                    staff_role.role_id
+                )))
+            }
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn set_role_permissions(
+        &self,
+        request: tonic::Request<proto::RolePermissions>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `set_role_permissions`");
+
+        let role_permissions = request.get_ref();
+
+        auth::authorize(&request, Scope::GuildWrite, Some(role_permissions.guild_id))?;
+
+        let mut actions = Vec::with_capacity(role_permissions.actions.len());
+        for &action in &role_permissions.actions {
+            actions.push(staff_action_from_proto(action)?);
+        }
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, role_permissions.guild_id).await?;
+
+        // Locking the staff role keeps concurrent updates from interleaving their permission sets.
+        let query =
+            "SELECT role_id FROM staff_role WHERE guild_id = $1 AND role_id = $2 FOR UPDATE";
+        let result = sqlx::query(query)
+            .bind(role_permissions.guild_id)
+            .bind(role_permissions.role_id)
+            .fetch_optional(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(Some(_)) => {}
+            Ok(None) => {
+                return Err(tonic::Status::not_found(format!(
+                    "role {} is not a staff role",
+                    role_permissions.role_id
+                )))
+            }
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        let query = "DELETE FROM permission WHERE guild_id = $1 AND role_id = $2";
+        let result = sqlx::query(query)
+            .bind(role_permissions.guild_id)
+            .bind(role_permissions.role_id)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        let query = "INSERT INTO permission SELECT $1, $2, action FROM unnest($3::staff_action[]) AS action ON CONFLICT DO NOTHING";
+        let result = sqlx::query(query)
+            .bind(role_permissions.guild_id)
+            .bind(role_permissions.role_id)
+            .bind(&actions)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn get_staff_roles(
+        &self,
+        request: tonic::Request<proto::Guild>,
+    ) -> Result<tonic::Response<proto::StaffRoles>, tonic::Status> {
+        info!("handling `get_staff_roles`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::GuildRead, Some(guild_id))?;
+        ensure_guild_active(&self.pool, guild_id).await?;
+
+        let result = sqlx::query_as::<_, models::staff::RolePermissions>(STAFF_ROLES_QUERY)
+            .bind(guild_id)
+            .fetch_all(&self.pool)
+            .await;
+
+        match result {
+            Ok(roles) => Ok(tonic::Response::new(proto::StaffRoles {
+                roles: roles
+                    .into_iter()
+                    .map(|role| proto::RolePermissions {
+                        guild_id,
+                        role_id: role.role_id,
+                        actions: role
+                            .actions
+                            .into_iter()
+                            .map(|action| permissions::proto::StaffAction::from(action) as i32)
+                            .collect(),
+                    })
+                    .collect(),
+            })),
+            Err(error) => Err(sqlx_error_to_tonic_status(&error)),
+        }
+    }
+
+    /// Answers from `role_ids` alone. The caller resolves which roles a member holds, so no user
+    /// is looked up here.
+    async fn check_permission(
+        &self,
+        request: tonic::Request<proto::PermissionCheck>,
+    ) -> Result<tonic::Response<proto::PermissionCheckResult>, tonic::Status> {
+        info!("handling `check_permission`");
+
+        let check = request.get_ref();
+
+        auth::authorize(&request, Scope::GuildRead, Some(check.guild_id))?;
+        ensure_guild_active(&self.pool, check.guild_id).await?;
+
+        let action = staff_action_from_proto(check.action)?;
+
+        let result =
+            permissions::is_allowed(&self.pool, check.guild_id, &check.role_ids, action).await;
+
+        match result {
+            Ok(allowed) => {
+                debug!(
+                    "roles {:?} {} perform {:?} in guild {}",
+                    check.role_ids,
+                    if allowed { "may" } else { "may not" },
+                    action,
+                    check.guild_id
+                );
+
+                Ok(tonic::Response::new(proto::PermissionCheckResult {
+                    allowed,
+                }))
+            }
+            Err(error) => Err(sqlx_error_to_tonic_status(&error)),
+        }
+    }
 }
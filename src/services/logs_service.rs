@@ -1,8 +1,18 @@
 use proto::logs_service_server;
 use sqlx::PgPool;
-use tracing::info;
+use tracing::{debug, info};
 
-use crate::{models, utils::sqlx_error_to_tonic_status};
+use crate::{
+    auth::{self, Scope},
+    models::{
+        self,
+        logs::{AuditEventType, AuditPayload, LogCategory, LogIgnoreKind},
+    },
+    pagination::{self, OffsetToken, PageToken},
+    utils::{
+        ensure_guild_active, lock_active_guild, sqlx_error_to_tonic_status, timestamp_to_datetime,
+    },
+};
 
 pub mod proto {
     tonic::include_proto!("logs");
@@ -17,6 +27,167 @@ impl From<models::logs::LogsSettings> for proto::LogsSettings {
             guild_id: value.guild_id,
             enabled: value.enabled,
             channel_id: value.channel_id,
+            retention_days: value.retention_days,
+        }
+    }
+}
+
+impl From<AuditEventType> for proto::AuditEventType {
+    fn from(value: AuditEventType) -> Self {
+        match value {
+            AuditEventType::MessageEdit => Self::MessageEdit,
+            AuditEventType::MessageDelete => Self::MessageDelete,
+            AuditEventType::MemberJoin => Self::MemberJoin,
+            AuditEventType::MemberLeave => Self::MemberLeave,
+            AuditEventType::MemberRoleUpdate => Self::MemberRoleUpdate,
+        }
+    }
+}
+
+impl From<proto::AuditEventType> for AuditEventType {
+    fn from(value: proto::AuditEventType) -> Self {
+        match value {
+            proto::AuditEventType::MessageEdit => Self::MessageEdit,
+            proto::AuditEventType::MessageDelete => Self::MessageDelete,
+            proto::AuditEventType::MemberJoin => Self::MemberJoin,
+            proto::AuditEventType::MemberLeave => Self::MemberLeave,
+            proto::AuditEventType::MemberRoleUpdate => Self::MemberRoleUpdate,
+        }
+    }
+}
+
+impl From<proto::audit_payload::Kind> for AuditPayload {
+    fn from(value: proto::audit_payload::Kind) -> Self {
+        match value {
+            proto::audit_payload::Kind::MessageEdit(edit) => Self::MessageEdit {
+                channel_id: edit.channel_id,
+                message_id: edit.message_id,
+                before: edit.before,
+                after: edit.after,
+            },
+            proto::audit_payload::Kind::MessageDelete(delete) => Self::MessageDelete {
+                channel_id: delete.channel_id,
+                message_id: delete.message_id,
+                content: delete.content,
+                attachment_urls: delete.attachment_urls,
+            },
+            proto::audit_payload::Kind::MemberJoin(join) => Self::MemberJoin {
+                account_created_at: join.account_created_at,
+            },
+            proto::audit_payload::Kind::MemberLeave(leave) => Self::MemberLeave {
+                role_ids: leave.role_ids,
+            },
+            proto::audit_payload::Kind::MemberRoleUpdate(update) => Self::MemberRoleUpdate {
+                added_role_ids: update.added_role_ids,
+                removed_role_ids: update.removed_role_ids,
+            },
+        }
+    }
+}
+
+impl From<&AuditPayload> for proto::AuditPayload {
+    fn from(value: &AuditPayload) -> Self {
+        let kind = match value {
+            AuditPayload::MessageEdit {
+                channel_id,
+                message_id,
+                before,
+                after,
+            } => proto::audit_payload::Kind::MessageEdit(proto::MessageEdit {
+                channel_id: *channel_id,
+                message_id: *message_id,
+                before: before.clone(),
+                after: after.clone(),
+            }),
+            AuditPayload::MessageDelete {
+                channel_id,
+                message_id,
+                content,
+                attachment_urls,
+            } => proto::audit_payload::Kind::MessageDelete(proto::MessageDelete {
+                channel_id: *channel_id,
+                message_id: *message_id,
+                content: content.clone(),
+                attachment_urls: attachment_urls.clone(),
+            }),
+            AuditPayload::MemberJoin { account_created_at } => {
+                proto::audit_payload::Kind::MemberJoin(proto::MemberJoin {
+                    account_created_at: *account_created_at,
+                })
+            }
+            AuditPayload::MemberLeave { role_ids } => {
+                proto::audit_payload::Kind::MemberLeave(proto::MemberLeave {
+                    role_ids: role_ids.clone(),
+                })
+            }
+            AuditPayload::MemberRoleUpdate {
+                added_role_ids,
+                removed_role_ids,
+            } => proto::audit_payload::Kind::MemberRoleUpdate(proto::MemberRoleUpdate {
+                added_role_ids: added_role_ids.clone(),
+                removed_role_ids: removed_role_ids.clone(),
+            }),
+        };
+
+        Self { kind: Some(kind) }
+    }
+}
+
+impl From<&models::logs::AuditEvent> for proto::AuditEvent {
+    fn from(value: &models::logs::AuditEvent) -> Self {
+        Self {
+            id: value.id,
+            guild_id: value.guild_id,
+            event_type: proto::AuditEventType::from(value.event_type).into(),
+            user_id: value.user_id,
+            channel_id: value.channel_id,
+            payload: Some(proto::AuditPayload::from(&value.payload.0)),
+            created_at: value.created_at.and_utc().timestamp(),
+        }
+    }
+}
+
+impl From<LogCategory> for proto::LogCategory {
+    fn from(value: LogCategory) -> Self {
+        match value {
+            LogCategory::Messages => Self::Messages,
+            LogCategory::Members => Self::Members,
+            LogCategory::Roles => Self::Roles,
+            LogCategory::Moderation => Self::Moderation,
+            LogCategory::Tickets => Self::Tickets,
+        }
+    }
+}
+
+impl From<proto::LogCategory> for LogCategory {
+    fn from(value: proto::LogCategory) -> Self {
+        match value {
+            proto::LogCategory::Messages => Self::Messages,
+            proto::LogCategory::Members => Self::Members,
+            proto::LogCategory::Roles => Self::Roles,
+            proto::LogCategory::Moderation => Self::Moderation,
+            proto::LogCategory::Tickets => Self::Tickets,
+        }
+    }
+}
+
+impl From<&models::logs::LogsRoute> for proto::LogsRoute {
+    fn from(value: &models::logs::LogsRoute) -> Self {
+        Self {
+            guild_id: value.guild_id,
+            category: proto::LogCategory::from(value.category).into(),
+            enabled: value.enabled,
+            channel_id: value.channel_id,
+        }
+    }
+}
+
+impl From<proto::LogIgnoreKind> for LogIgnoreKind {
+    fn from(value: proto::LogIgnoreKind) -> Self {
+        match value {
+            proto::LogIgnoreKind::Channel => Self::Channel,
+            proto::LogIgnoreKind::User => Self::User,
+            proto::LogIgnoreKind::Role => Self::Role,
         }
     }
 }
@@ -32,6 +203,26 @@ impl LogsService {
     }
 }
 
+fn log_ignore_kind_from_proto(kind: i32) -> Result<LogIgnoreKind, tonic::Status> {
+    match proto::LogIgnoreKind::try_from(kind) {
+        Ok(kind) => Ok(LogIgnoreKind::from(kind)),
+        Err(_) => Err(tonic::Status::invalid_argument(format!(
+            "unknown log ignore kind {}",
+            kind
+        ))),
+    }
+}
+
+fn log_category_from_proto(category: i32) -> Result<LogCategory, tonic::Status> {
+    match proto::LogCategory::try_from(category) {
+        Ok(category) => Ok(LogCategory::from(category)),
+        Err(_) => Err(tonic::Status::invalid_argument(format!(
+            "unknown log category {}",
+            category
+        ))),
+    }
+}
+
 #[tonic::async_trait]
 impl logs_service_server::LogsService for LogsService {
     async fn create_or_update_settings(
@@ -42,12 +233,22 @@ impl logs_service_server::LogsService for LogsService {
 
         let settings = request.get_ref();
 
-        let query = "INSERT INTO logs_settings VALUES ($1, $2, $3) ON CONFLICT (guild_id) DO UPDATE SET enabled = $2, channel_id = $3";
+        auth::authorize(&request, Scope::LogsWrite, Some(settings.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, settings.guild_id).await?;
+
+        let query = "INSERT INTO logs_settings VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id) DO UPDATE SET enabled = $2, channel_id = $3, retention_days = $4";
         let result = sqlx::query(query)
             .bind(settings.guild_id)
             .bind(settings.enabled)
             .bind(settings.channel_id)
-            .execute(&self.pool)
+            .bind(settings.retention_days)
+            .execute(&mut *transaction)
             .await;
 
         match result {
@@ -55,6 +256,11 @@ impl logs_service_server::LogsService for LogsService {
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
         }
 
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
         Ok(tonic::Response::new(()))
     }
 
@@ -66,6 +272,9 @@ impl logs_service_server::LogsService for LogsService {
 
         let guild_id = request.get_ref().guild_id;
 
+        auth::authorize(&request, Scope::LogsRead, Some(guild_id))?;
+        ensure_guild_active(&self.pool, guild_id).await?;
+
         let query = "SELECT * FROM logs_settings WHERE guild_id = $1";
         let result = sqlx::query_as::<_, models::logs::LogsSettings>(query)
             .bind(guild_id)
@@ -79,4 +288,573 @@ impl logs_service_server::LogsService for LogsService {
 
         Ok(tonic::Response::new(settings.into()))
     }
+
+    async fn record_audit_event(
+        &self,
+        request: tonic::Request<proto::NewAuditEvent>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `record_audit_event`");
+
+        auth::authorize(&request, Scope::LogsWrite, Some(request.get_ref().guild_id))?;
+
+        let new_event = request.into_inner();
+
+        let payload = match new_event.payload.and_then(|payload| payload.kind) {
+            Some(kind) => AuditPayload::from(kind),
+            None => return Err(tonic::Status::invalid_argument("payload is required")),
+        };
+
+        let created_at = match new_event.occurred_at {
+            Some(timestamp) => Some(timestamp_to_datetime(timestamp)?),
+            None => None,
+        };
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, new_event.guild_id).await?;
+
+        // Events touching an ignored channel, user or role are dropped here so every shard
+        // applies the same ignore lists.
+        let query = "INSERT INTO audit_event (guild_id, event_type, user_id, channel_id, payload, created_at) \
+            SELECT $1, $2, $3, $4, $5, COALESCE($6, NOW()) \
+            WHERE NOT EXISTS (SELECT 1 FROM logs_ignored_channel WHERE guild_id = $1 AND channel_id = $4) \
+            AND NOT EXISTS (SELECT 1 FROM logs_ignored_user WHERE guild_id = $1 AND user_id = $3) \
+            AND NOT EXISTS (SELECT 1 FROM logs_ignored_role WHERE guild_id = $1 AND role_id = ANY($7))";
+        let result = sqlx::query(query)
+            .bind(new_event.guild_id)
+            .bind(payload.event_type())
+            .bind(new_event.user_id)
+            .bind(payload.channel_id())
+            .bind(sqlx::types::Json(&payload))
+            .bind(created_at)
+            .bind(&new_event.role_ids)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(result) if result.rows_affected() == 0 => {
+                debug!(
+                    "dropped {:?} audit event in guild {} matching an ignore list",
+                    payload.event_type(),
+                    new_event.guild_id
+                );
+            }
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn get_audit_events(
+        &self,
+        request: tonic::Request<proto::AuditEventsRequest>,
+    ) -> Result<tonic::Response<proto::AuditEvents>, tonic::Status> {
+        info!("handling `get_audit_events`");
+
+        let events_request = request.get_ref();
+
+        auth::authorize(&request, Scope::LogsRead, Some(events_request.guild_id))?;
+        ensure_guild_active(&self.pool, events_request.guild_id).await?;
+
+        let mut event_types = Vec::with_capacity(events_request.event_types.len());
+        for &event_type in &events_request.event_types {
+            match proto::AuditEventType::try_from(event_type) {
+                Ok(event_type) => event_types.push(AuditEventType::from(event_type)),
+                Err(_) => {
+                    return Err(tonic::Status::invalid_argument(format!(
+                        "unknown audit event type {}",
+                        event_type
+                    )))
+                }
+            }
+        }
+
+        let start = match events_request.start {
+            Some(timestamp) => Some(timestamp_to_datetime(timestamp)?),
+            None => None,
+        };
+        let end = match events_request.end {
+            Some(timestamp) => Some(timestamp_to_datetime(timestamp)?),
+            None => None,
+        };
+
+        let sort_direction = pagination::sort_direction_from_proto(events_request.sort_direction)?;
+        let page_size = pagination::page_size(events_request.page_size);
+        let page_token = PageToken::decode(&events_request.page_token, sort_direction)?;
+
+        let query = format!(
+            "SELECT * FROM audit_event WHERE guild_id = $1 \
+            AND ($2::bigint IS NULL OR user_id = $2) \
+            AND (cardinality($3::audit_event_type[]) = 0 OR event_type = ANY($3)) \
+            AND ($4::timestamp IS NULL OR created_at >= $4) \
+            AND ($5::timestamp IS NULL OR created_at < $5) \
+            AND ($6::timestamp IS NULL OR (created_at, id) {} ($6, $7)) \
+            ORDER BY created_at {}, id {} LIMIT $8",
+            sort_direction.comparison(),
+            sort_direction.keyword(),
+            sort_direction.keyword()
+        );
+        let result = sqlx::query_as::<_, models::logs::AuditEvent>(&query)
+            .bind(events_request.guild_id)
+            .bind(events_request.user_id)
+            .bind(&event_types)
+            .bind(start)
+            .bind(end)
+            .bind(page_token.map(|token| token.created_at))
+            .bind(page_token.map(|token| token.id))
+            .bind(i64::from(page_size) + 1)
+            .fetch_all(&self.pool)
+            .await;
+
+        let mut events = match result {
+            Ok(events) => events,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let next_page_token =
+            pagination::next_page_token(&mut events, page_size, sort_direction, |row| PageToken {
+                created_at: row.created_at,
+                id: row.id,
+            });
+
+        let events = events.iter().map(proto::AuditEvent::from).collect();
+
+        Ok(tonic::Response::new(proto::AuditEvents {
+            events,
+            next_page_token,
+        }))
+    }
+
+    async fn set_log_route(
+        &self,
+        request: tonic::Request<proto::LogsRoute>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `set_log_route`");
+
+        let route = request.get_ref();
+
+        auth::authorize(&request, Scope::LogsWrite, Some(route.guild_id))?;
+
+        let category = log_category_from_proto(route.category)?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, route.guild_id).await?;
+
+        let query = "INSERT INTO logs_route VALUES ($1, $2, $3, $4) ON CONFLICT (guild_id, category) DO UPDATE SET enabled = $3, channel_id = $4";
+        let result = sqlx::query(query)
+            .bind(route.guild_id)
+            .bind(category)
+            .bind(route.enabled)
+            .bind(route.channel_id)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn delete_log_route(
+        &self,
+        request: tonic::Request<proto::LogsRouteRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `delete_log_route`");
+
+        let route_request = request.get_ref();
+
+        auth::authorize(&request, Scope::LogsWrite, Some(route_request.guild_id))?;
+
+        let category = log_category_from_proto(route_request.category)?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, route_request.guild_id).await?;
+
+        let query = "DELETE FROM logs_route WHERE guild_id = $1 AND category = $2";
+        let result = sqlx::query(query)
+            .bind(route_request.guild_id)
+            .bind(category)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn get_log_routes(
+        &self,
+        request: tonic::Request<proto::LogsSettingsRequest>,
+    ) -> Result<tonic::Response<proto::LogsRoutes>, tonic::Status> {
+        info!("handling `get_log_routes`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::LogsRead, Some(guild_id))?;
+        ensure_guild_active(&self.pool, guild_id).await?;
+
+        let query = "SELECT * FROM logs_route WHERE guild_id = $1 ORDER BY category";
+        let result = sqlx::query_as::<_, models::logs::LogsRoute>(query)
+            .bind(guild_id)
+            .fetch_all(&self.pool)
+            .await;
+
+        let routes = match result {
+            Ok(routes) => routes,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+        .iter()
+        .map(proto::LogsRoute::from)
+        .collect();
+
+        Ok(tonic::Response::new(proto::LogsRoutes { routes }))
+    }
+
+    async fn resolve_log_channel(
+        &self,
+        request: tonic::Request<proto::LogsRouteRequest>,
+    ) -> Result<tonic::Response<proto::ResolvedLogChannel>, tonic::Status> {
+        info!("handling `resolve_log_channel`");
+
+        let route_request = request.get_ref();
+
+        auth::authorize(&request, Scope::LogsRead, Some(route_request.guild_id))?;
+        ensure_guild_active(&self.pool, route_request.guild_id).await?;
+
+        let category = log_category_from_proto(route_request.category)?;
+
+        let query = "SELECT COALESCE(logs_settings.enabled, false) AND COALESCE(logs_route.enabled, true) AS enabled, \
+            COALESCE(logs_route.channel_id, logs_settings.channel_id, 0) AS channel_id \
+            FROM logs_settings LEFT JOIN logs_route \
+            ON logs_route.guild_id = logs_settings.guild_id AND logs_route.category = $2 \
+            WHERE logs_settings.guild_id = $1";
+        let result = sqlx::query_as::<_, models::logs::ResolvedLogChannel>(query)
+            .bind(route_request.guild_id)
+            .bind(category)
+            .fetch_one(&self.pool)
+            .await;
+
+        let resolved = match result {
+            Ok(resolved) => resolved,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        Ok(tonic::Response::new(proto::ResolvedLogChannel {
+            enabled: resolved.enabled,
+            channel_id: resolved.channel_id,
+        }))
+    }
+
+    async fn add_log_ignore(
+        &self,
+        request: tonic::Request<proto::LogIgnore>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `add_log_ignore`");
+
+        let ignore = request.get_ref();
+
+        auth::authorize(&request, Scope::LogsWrite, Some(ignore.guild_id))?;
+
+        let kind = log_ignore_kind_from_proto(ignore.kind)?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, ignore.guild_id).await?;
+
+        let query = format!(
+            "INSERT INTO {} (guild_id, {}) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+            kind.table(),
+            kind.column()
+        );
+        let result = sqlx::query(&query)
+            .bind(ignore.guild_id)
+            .bind(ignore.target_id)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn remove_log_ignore(
+        &self,
+        request: tonic::Request<proto::LogIgnore>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `remove_log_ignore`");
+
+        let ignore = request.get_ref();
+
+        auth::authorize(&request, Scope::LogsWrite, Some(ignore.guild_id))?;
+
+        let kind = log_ignore_kind_from_proto(ignore.kind)?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, ignore.guild_id).await?;
+
+        let query = format!(
+            "DELETE FROM {} WHERE guild_id = $1 AND {} = $2",
+            kind.table(),
+            kind.column()
+        );
+        let result = sqlx::query(&query)
+            .bind(ignore.guild_id)
+            .bind(ignore.target_id)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn get_log_ignores(
+        &self,
+        request: tonic::Request<proto::LogsSettingsRequest>,
+    ) -> Result<tonic::Response<proto::LogIgnores>, tonic::Status> {
+        info!("handling `get_log_ignores`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::LogsRead, Some(guild_id))?;
+        ensure_guild_active(&self.pool, guild_id).await?;
+
+        let mut ignores = proto::LogIgnores::default();
+        for kind in [
+            LogIgnoreKind::Channel,
+            LogIgnoreKind::User,
+            LogIgnoreKind::Role,
+        ] {
+            let query = format!(
+                "SELECT {} FROM {} WHERE guild_id = $1 ORDER BY {}",
+                kind.column(),
+                kind.table(),
+                kind.column()
+            );
+            let result = sqlx::query_scalar::<_, i64>(&query)
+                .bind(guild_id)
+                .fetch_all(&self.pool)
+                .await;
+
+            let ids = match result {
+                Ok(ids) => ids,
+                Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+            };
+
+            match kind {
+                LogIgnoreKind::Channel => ignores.channel_ids = ids,
+                LogIgnoreKind::User => ignores.user_ids = ids,
+                LogIgnoreKind::Role => ignores.role_ids = ids,
+            }
+        }
+
+        Ok(tonic::Response::new(ignores))
+    }
+
+    async fn get_prune_stats(
+        &self,
+        request: tonic::Request<()>,
+    ) -> Result<tonic::Response<proto::PruneStats>, tonic::Status> {
+        info!("handling `get_prune_stats`");
+
+        auth::authorize(&request, Scope::Admin, None)?;
+
+        let query = "SELECT * FROM audit_prune_run WHERE finished_at IS NOT NULL ORDER BY finished_at DESC LIMIT 1";
+        let result = sqlx::query_as::<_, models::logs::AuditPruneRun>(query)
+            .fetch_one(&self.pool)
+            .await;
+
+        let run = match result {
+            Ok(run) => run,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        Ok(tonic::Response::new(proto::PruneStats {
+            started_at: run.started_at.and_utc().timestamp(),
+            finished_at: run
+                .finished_at
+                .map(|finished_at| finished_at.and_utc().timestamp()),
+            rows_pruned: run.rows_pruned,
+        }))
+    }
+
+    async fn search_audit_events(
+        &self,
+        request: tonic::Request<proto::AuditEventSearchRequest>,
+    ) -> Result<tonic::Response<proto::AuditEventSearchResults>, tonic::Status> {
+        info!("handling `search_audit_events`");
+
+        let search_request = request.get_ref();
+
+        auth::authorize(&request, Scope::LogsRead, search_request.guild_id)?;
+        if let Some(guild_id) = search_request.guild_id {
+            ensure_guild_active(&self.pool, guild_id).await?;
+        }
+
+        if search_request.query.trim().is_empty() {
+            return Err(tonic::Status::invalid_argument("query must not be empty"));
+        }
+
+        let start = match search_request.start {
+            Some(timestamp) => Some(timestamp_to_datetime(timestamp)?),
+            None => None,
+        };
+        let end = match search_request.end {
+            Some(timestamp) => Some(timestamp_to_datetime(timestamp)?),
+            None => None,
+        };
+
+        let page_size = pagination::page_size(search_request.page_size);
+        let page_token = OffsetToken::decode(&search_request.page_token)?;
+
+        let query = "SELECT audit_event.*, ts_rank(audit_event.search_vector, search_query) AS rank \
+            FROM audit_event, websearch_to_tsquery('english', $1) search_query \
+            WHERE audit_event.search_vector @@ search_query \
+            AND NOT EXISTS (SELECT 1 FROM guild WHERE guild.guild_id = audit_event.guild_id AND guild.deleted_at IS NOT NULL) \
+            AND ($2::bigint IS NULL OR audit_event.guild_id = $2) \
+            AND ($3::bigint IS NULL OR audit_event.user_id = $3) \
+            AND ($4::timestamp IS NULL OR audit_event.created_at >= $4) \
+            AND ($5::timestamp IS NULL OR audit_event.created_at < $5) \
+            ORDER BY rank DESC, audit_event.created_at DESC, audit_event.id DESC LIMIT $6 OFFSET $7";
+        let result = sqlx::query_as::<_, models::logs::AuditEventSearchHit>(query)
+            .bind(&search_request.query)
+            .bind(search_request.guild_id)
+            .bind(search_request.user_id)
+            .bind(start)
+            .bind(end)
+            .bind(i64::from(page_size) + 1)
+            .bind(page_token.offset)
+            .fetch_all(&self.pool)
+            .await;
+
+        let mut hits = match result {
+            Ok(hits) => hits,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let next_page_token = page_token.next_page_token(&mut hits, page_size);
+
+        let hits = hits
+            .iter()
+            .map(|hit| proto::AuditEventSearchHit {
+                event: Some(proto::AuditEvent::from(&hit.event)),
+                rank: hit.rank,
+            })
+            .collect();
+
+        Ok(tonic::Response::new(proto::AuditEventSearchResults {
+            hits,
+            next_page_token,
+        }))
+    }
+
+    async fn reset_settings(
+        &self,
+        request: tonic::Request<proto::LogsSettingsRequest>,
+    ) -> Result<tonic::Response<proto::LogsSettings>, tonic::Status> {
+        info!("handling `reset_settings`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::LogsWrite, Some(guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, guild_id).await?;
+
+        let query = "INSERT INTO logs_settings (guild_id) VALUES ($1) ON CONFLICT (guild_id) \
+            DO UPDATE SET enabled = DEFAULT, channel_id = DEFAULT, retention_days = DEFAULT RETURNING *";
+        let result = sqlx::query_as::<_, models::logs::LogsSettings>(query)
+            .bind(guild_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let settings = match result {
+            Ok(settings) => settings,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        for table in [
+            "logs_route",
+            LogIgnoreKind::Channel.table(),
+            LogIgnoreKind::User.table(),
+            LogIgnoreKind::Role.table(),
+        ] {
+            let query = format!("DELETE FROM {} WHERE guild_id = $1", table);
+            let result = sqlx::query(&query)
+                .bind(guild_id)
+                .execute(&mut *transaction)
+                .await;
+
+            match result {
+                Ok(_) => {}
+                Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+            }
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(settings.into()))
+    }
 }
@@ -1,3 +1,5 @@
+pub mod admin_service;
+pub mod events_service;
 pub mod guild_service;
 pub mod logs_service;
 pub mod moderation_service;
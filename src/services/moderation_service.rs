@@ -1,8 +1,26 @@
+use std::pin::Pin;
+
 use proto::moderation_service_server;
 use sqlx::PgPool;
-use tracing::info;
-
-use crate::{models, utils::sqlx_error_to_tonic_status};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream, StreamExt,
+};
+use tracing::{error, info, warn};
+
+use crate::{
+    auth::{self, Scope},
+    events::{Event, EventBus, EventKind},
+    models::{
+        self,
+        moderation::{AutomodAction, InfractionType},
+        staff::StaffAction,
+    },
+    pagination::{self, PageToken},
+    permissions,
+    utils::{ensure_guild_active, lock_active_guild, sqlx_error_to_tonic_status},
+};
 
 pub mod proto {
     tonic::include_proto!("moderation");
@@ -19,6 +37,7 @@ impl From<models::moderation::AutomodSettings> for proto::AutomodSettings {
             autoban_threshold: value.autoban_threshold,
             autokick_enabled: value.autokick_enabled,
             autokick_threshold: value.autokick_threshold,
+            warn_expiry_days: value.warn_expiry_days,
         }
     }
 }
@@ -28,10 +47,12 @@ impl From<models::moderation::Warn> for proto::Warn {
         Self {
             id: value.id,
             guild_id: value.guild_id,
+            case_number: value.case_number,
             staff_member_id: value.staff_member_id,
-            target_user_id: value.targer_user_id,
+            target_user_id: value.target_user_id,
             reason: value.reason,
             created_at: value.created_at.and_utc().timestamp(),
+            active: value.active,
         }
     }
 }
@@ -41,27 +62,142 @@ impl From<&models::moderation::Warn> for proto::Warn {
         Self {
             id: value.id,
             guild_id: value.guild_id,
+            case_number: value.case_number,
+            staff_member_id: value.staff_member_id,
+            target_user_id: value.target_user_id,
+            reason: value.reason.clone(),
+            created_at: value.created_at.and_utc().timestamp(),
+            active: value.active,
+        }
+    }
+}
+
+impl From<InfractionType> for proto::InfractionType {
+    fn from(value: InfractionType) -> Self {
+        match value {
+            InfractionType::Warn => Self::Warn,
+            InfractionType::Timeout => Self::Timeout,
+            InfractionType::Kick => Self::Kick,
+            InfractionType::Ban => Self::Ban,
+            InfractionType::Unban => Self::Unban,
+            InfractionType::Note => Self::Note,
+        }
+    }
+}
+
+impl From<proto::InfractionType> for InfractionType {
+    fn from(value: proto::InfractionType) -> Self {
+        match value {
+            proto::InfractionType::Warn => Self::Warn,
+            proto::InfractionType::Timeout => Self::Timeout,
+            proto::InfractionType::Kick => Self::Kick,
+            proto::InfractionType::Ban => Self::Ban,
+            proto::InfractionType::Unban => Self::Unban,
+            proto::InfractionType::Note => Self::Note,
+        }
+    }
+}
+
+impl From<&models::moderation::Infraction> for proto::Infraction {
+    fn from(value: &models::moderation::Infraction) -> Self {
+        Self {
+            id: value.id,
+            guild_id: value.guild_id,
+            case_number: value.case_number,
+            infraction_type: proto::InfractionType::from(value.infraction_type).into(),
             staff_member_id: value.staff_member_id,
-            target_user_id: value.targer_user_id,
+            target_user_id: value.target_user_id,
             reason: value.reason.clone(),
+            expires_at: value
+                .expires_at
+                .map(|expires_at| expires_at.and_utc().timestamp()),
+            triggered_by: value.triggered_by,
             created_at: value.created_at.and_utc().timestamp(),
+            lifted_at: value
+                .lifted_at
+                .map(|lifted_at| lifted_at.and_utc().timestamp()),
         }
     }
 }
 
+const EXPIRED_INFRACTIONS_CAPACITY: usize = 256;
+
 #[derive(Debug)]
 pub struct ModerationService {
     pool: PgPool,
+    expired_infractions: broadcast::Sender<models::moderation::Infraction>,
 }
 
 impl ModerationService {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, events: EventBus) -> Self {
+        let (expired_infractions, _) = broadcast::channel(EXPIRED_INFRACTIONS_CAPACITY);
+
+        tokio::spawn(forward_expired_infractions(
+            pool.clone(),
+            events.subscribe(),
+            expired_infractions.clone(),
+        ));
+
+        Self {
+            pool,
+            expired_infractions,
+        }
+    }
+}
+
+/// Fetches each expired infraction once and fans it out to this replica's watchers, so watching
+/// costs no queries per subscriber.
+async fn forward_expired_infractions(
+    pool: PgPool,
+    mut events: broadcast::Receiver<Event>,
+    expired_infractions: broadcast::Sender<models::moderation::Infraction>,
+) {
+    loop {
+        let event = match events.recv().await {
+            Ok(event) => event,
+            Err(RecvError::Lagged(skipped)) => {
+                warn!(
+                    "expired infraction forwarder lagged, skipped {} events",
+                    skipped
+                );
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        if event.kind != EventKind::InfractionExpired || expired_infractions.receiver_count() == 0 {
+            continue;
+        }
+
+        let Some(infraction_id) = event.entity_id else {
+            continue;
+        };
+
+        let query = "SELECT * FROM infraction WHERE id = $1";
+        let result = sqlx::query_as::<_, models::moderation::Infraction>(query)
+            .bind(infraction_id)
+            .fetch_optional(&pool)
+            .await;
+
+        match result {
+            // Sending only fails when every watcher has gone, in which case nobody needs it.
+            Ok(Some(infraction)) => {
+                let _ = expired_infractions.send(infraction);
+            }
+            Ok(None) => {}
+            Err(error) => error!(
+                "failed to fetch expired infraction {}: {}",
+                infraction_id, error
+            ),
+        }
     }
 }
 
 #[tonic::async_trait]
 impl moderation_service_server::ModerationService for ModerationService {
+    type WatchExpiredInfractionsStream =
+        Pin<Box<dyn Stream<Item = Result<proto::Infraction, tonic::Status>> + Send>>;
+
     async fn create_or_update_settings(
         &self,
         request: tonic::Request<proto::AutomodSettings>,
@@ -70,16 +206,26 @@ impl moderation_service_server::ModerationService for ModerationService {
 
         let settings = request.get_ref();
 
+        auth::authorize(&request, Scope::ModerationWrite, Some(settings.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, settings.guild_id).await?;
+
         let query =
-            "INSERT INTO automod_settings VALUES ($1, $2, $3, $4, $5) ON CONFLICT (guild_id) \
-            DO UPDATE SET autoban_enabled = $2, autoban_threshold = $3, autokick_enabled = $4, autokick_threshold = $5";
+            "INSERT INTO automod_settings VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (guild_id) \
+            DO UPDATE SET autoban_enabled = $2, autoban_threshold = $3, autokick_enabled = $4, autokick_threshold = $5, warn_expiry_days = $6";
         let result = sqlx::query(query)
             .bind(settings.guild_id)
             .bind(settings.autoban_enabled)
             .bind(settings.autoban_threshold)
             .bind(settings.autokick_enabled)
             .bind(settings.autokick_threshold)
-            .execute(&self.pool)
+            .bind(settings.warn_expiry_days)
+            .execute(&mut *transaction)
             .await;
 
         match result {
@@ -87,6 +233,11 @@ impl moderation_service_server::ModerationService for ModerationService {
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
         }
 
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
         Ok(tonic::Response::new(()))
     }
 
@@ -98,6 +249,9 @@ impl moderation_service_server::ModerationService for ModerationService {
 
         let guild_id = request.get_ref().guild_id;
 
+        auth::authorize(&request, Scope::ModerationRead, Some(guild_id))?;
+        ensure_guild_active(&self.pool, guild_id).await?;
+
         let query = "SELECT * FROM automod_settings WHERE guild_id = $1";
         let result = sqlx::query_as::<_, models::moderation::AutomodSettings>(query)
             .bind(guild_id)
@@ -115,18 +269,34 @@ impl moderation_service_server::ModerationService for ModerationService {
     async fn create_warn(
         &self,
         request: tonic::Request<proto::NewWarn>,
-    ) -> Result<tonic::Response<()>, tonic::Status> {
+    ) -> Result<tonic::Response<proto::WarnResult>, tonic::Status> {
         info!("handling `create_warn`");
 
-        let new_ticket = request.get_ref();
+        let new_warn = request.get_ref();
 
-        let query = "INSERT INTO warn (guild_id, staff_member_id, target_user_id, reason) VALUES ($1, $2, $3, $4)";
+        auth::authorize(&request, Scope::ModerationWrite, Some(new_warn.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, new_warn.guild_id).await?;
+
+        permissions::require(
+            &mut *transaction,
+            new_warn.guild_id,
+            new_warn.staff.as_ref(),
+            StaffAction::Warn,
+        )
+        .await?;
+
+        // Serialise warns against the same user so concurrent requests see each other's counts.
+        let query = "SELECT pg_advisory_xact_lock($1 # $2)";
         let result = sqlx::query(query)
-            .bind(new_ticket.guild_id)
-            .bind(new_ticket.staff_member_id)
-            .bind(new_ticket.target_user_id)
-            .bind(&new_ticket.reason)
-            .execute(&self.pool)
+            .bind(new_warn.guild_id)
+            .bind(new_warn.target_user_id)
+            .execute(&mut *transaction)
             .await;
 
         match result {
@@ -134,7 +304,89 @@ impl moderation_service_server::ModerationService for ModerationService {
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
         }
 
-        Ok(tonic::Response::new(()))
+        let query = "INSERT INTO infraction (guild_id, infraction_type, staff_member_id, target_user_id, reason) VALUES ($1, 'warn', $2, $3, $4) RETURNING id";
+        let result = sqlx::query_scalar::<_, i32>(query)
+            .bind(new_warn.guild_id)
+            .bind(new_warn.staff_member_id)
+            .bind(new_warn.target_user_id)
+            .bind(&new_warn.reason)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let warn_id = match result {
+            Ok(warn_id) => warn_id,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let query = "SELECT * FROM warn WHERE id = $1";
+        let result = sqlx::query_as::<_, models::moderation::Warn>(query)
+            .bind(warn_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let warn = match result {
+            Ok(warn) => warn,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let query = "SELECT * FROM automod_settings WHERE guild_id = $1";
+        let result = sqlx::query_as::<_, models::moderation::AutomodSettings>(query)
+            .bind(warn.guild_id)
+            .fetch_optional(&mut *transaction)
+            .await;
+
+        let settings = match result {
+            Ok(settings) => settings,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let query =
+            "SELECT COUNT(*) FROM warn WHERE guild_id = $1 AND target_user_id = $2 AND active";
+        let result = sqlx::query_scalar::<_, i64>(query)
+            .bind(warn.guild_id)
+            .bind(warn.target_user_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let warn_count = match result {
+            Ok(warn_count) => warn_count,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let action = settings.and_then(|settings| settings.escalation(warn_count));
+
+        if let Some(action) = action {
+            let query = "INSERT INTO infraction (guild_id, infraction_type, target_user_id, reason, triggered_by) VALUES ($1, $2, $3, $4, $5)";
+            let result = sqlx::query(query)
+                .bind(warn.guild_id)
+                .bind(InfractionType::from(action))
+                .bind(warn.target_user_id)
+                .bind(format!("Automod: reached {} warns", warn_count))
+                .bind(warn.id)
+                .execute(&mut *transaction)
+                .await;
+
+            match result {
+                Ok(_) => {}
+                Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+            }
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        let action = match action {
+            Some(AutomodAction::Kick) => proto::AutomodAction::Kick,
+            Some(AutomodAction::Ban) => proto::AutomodAction::Ban,
+            None => proto::AutomodAction::None,
+        };
+
+        Ok(tonic::Response::new(proto::WarnResult {
+            warn: Some(warn.into()),
+            action: action.into(),
+        }))
     }
 
     async fn get_warn(
@@ -145,6 +397,9 @@ impl moderation_service_server::ModerationService for ModerationService {
 
         let warn_request = request.get_ref();
 
+        auth::authorize(&request, Scope::ModerationRead, Some(warn_request.guild_id))?;
+        ensure_guild_active(&self.pool, warn_request.guild_id).await?;
+
         let query =
             "SELECT * FROM warn WHERE guild_id = $1 AND target_user_id = $2 ORDER BY created_at ASC";
         let result = sqlx::query_as::<_, models::moderation::Warn>(query)
@@ -169,23 +424,47 @@ impl moderation_service_server::ModerationService for ModerationService {
 
         let warn_request = request.get_ref();
 
-        let query =
-            "SELECT * FROM warn WHERE guild_id = $1 AND target_user_id = $2 ORDER BY created_at ASC LIMIT 5";
-        let result = sqlx::query_as::<_, models::moderation::Warn>(query)
+        auth::authorize(&request, Scope::ModerationRead, Some(warn_request.guild_id))?;
+        ensure_guild_active(&self.pool, warn_request.guild_id).await?;
+
+        let sort_direction = pagination::sort_direction_from_proto(warn_request.sort_direction)?;
+        let page_size = pagination::page_size(warn_request.page_size);
+        let page_token = PageToken::decode(&warn_request.page_token, sort_direction)?;
+
+        let query = format!(
+            "SELECT * FROM warn WHERE guild_id = $1 AND target_user_id = $2 \
+            AND ($3::timestamp IS NULL OR (created_at, id) {} ($3, $4)) \
+            ORDER BY created_at {}, id {} LIMIT $5",
+            sort_direction.comparison(),
+            sort_direction.keyword(),
+            sort_direction.keyword()
+        );
+        let result = sqlx::query_as::<_, models::moderation::Warn>(&query)
             .bind(warn_request.guild_id)
             .bind(warn_request.target_user_id)
+            .bind(page_token.map(|token| token.created_at))
+            .bind(page_token.map(|token| token.id))
+            .bind(i64::from(page_size) + 1)
             .fetch_all(&self.pool)
             .await;
 
-        let warns = match result {
+        let mut warns = match result {
             Ok(warns) => warns,
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
-        }
-        .iter()
-        .map(|warn| warn.into())
-        .collect();
+        };
+
+        let next_page_token =
+            pagination::next_page_token(&mut warns, page_size, sort_direction, |row| PageToken {
+                created_at: row.created_at,
+                id: row.id.into(),
+            });
 
-        Ok(tonic::Response::new(proto::Warns { warns }))
+        let warns = warns.iter().map(proto::Warn::from).collect();
+
+        Ok(tonic::Response::new(proto::Warns {
+            warns,
+            next_page_token,
+        }))
     }
 
     async fn delete_warn(
@@ -196,12 +475,33 @@ impl moderation_service_server::ModerationService for ModerationService {
 
         let warn_request = request.get_ref();
 
+        auth::authorize(
+            &request,
+            Scope::ModerationWrite,
+            Some(warn_request.guild_id),
+        )?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, warn_request.guild_id).await?;
+
+        permissions::require(
+            &mut *transaction,
+            warn_request.guild_id,
+            warn_request.staff.as_ref(),
+            StaffAction::ManageWarns,
+        )
+        .await?;
+
         let query =
-            "DELETE FROM warn WHERE guild_id = $1 AND target_user_id = $2 AND created_at = (SELECT MAX (created_at) FROM warn WHERE guild_id = $1 AND target_user_id = $2)";
+            "DELETE FROM infraction WHERE id = (SELECT id FROM infraction WHERE infraction_type = 'warn' AND guild_id = $1 AND target_user_id = $2 ORDER BY created_at DESC, id DESC LIMIT 1)";
         let result = sqlx::query(query)
             .bind(warn_request.guild_id)
             .bind(warn_request.target_user_id)
-            .execute(&self.pool)
+            .execute(&mut *transaction)
             .await;
 
         match result {
@@ -209,6 +509,411 @@ impl moderation_service_server::ModerationService for ModerationService {
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
         }
 
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
         Ok(tonic::Response::new(()))
     }
+
+    async fn get_warn_by_id(
+        &self,
+        request: tonic::Request<proto::WarnIdRequest>,
+    ) -> Result<tonic::Response<proto::Warn>, tonic::Status> {
+        info!("handling `get_warn_by_id`");
+
+        let warn_request = request.get_ref();
+
+        auth::authorize(&request, Scope::ModerationRead, Some(warn_request.guild_id))?;
+        ensure_guild_active(&self.pool, warn_request.guild_id).await?;
+
+        let query = "SELECT * FROM warn WHERE id = $1 AND guild_id = $2";
+        let result = sqlx::query_as::<_, models::moderation::Warn>(query)
+            .bind(warn_request.id)
+            .bind(warn_request.guild_id)
+            .fetch_one(&self.pool)
+            .await;
+
+        let warn = match result {
+            Ok(warn) => warn,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        Ok(tonic::Response::new(warn.into()))
+    }
+
+    async fn update_warn_reason(
+        &self,
+        request: tonic::Request<proto::UpdateWarnReason>,
+    ) -> Result<tonic::Response<proto::Warn>, tonic::Status> {
+        info!("handling `update_warn_reason`");
+
+        let update = request.get_ref();
+
+        auth::authorize(&request, Scope::ModerationWrite, Some(update.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, update.guild_id).await?;
+
+        permissions::require(
+            &mut *transaction,
+            update.guild_id,
+            update.staff.as_ref(),
+            StaffAction::ManageWarns,
+        )
+        .await?;
+
+        let query = "UPDATE infraction SET reason = $3 WHERE infraction_type = 'warn' AND id = $1 AND guild_id = $2 RETURNING id";
+        let result = sqlx::query_scalar::<_, i32>(query)
+            .bind(update.id)
+            .bind(update.guild_id)
+            .bind(&update.reason)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let warn_id = match result {
+            Ok(warn_id) => warn_id,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let query = "SELECT * FROM warn WHERE id = $1";
+        let result = sqlx::query_as::<_, models::moderation::Warn>(query)
+            .bind(warn_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let warn = match result {
+            Ok(warn) => warn,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(warn.into()))
+    }
+
+    async fn delete_warn_by_id(
+        &self,
+        request: tonic::Request<proto::WarnIdRequest>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `delete_warn_by_id`");
+
+        let warn_request = request.get_ref();
+
+        auth::authorize(
+            &request,
+            Scope::ModerationWrite,
+            Some(warn_request.guild_id),
+        )?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, warn_request.guild_id).await?;
+
+        permissions::require(
+            &mut *transaction,
+            warn_request.guild_id,
+            warn_request.staff.as_ref(),
+            StaffAction::ManageWarns,
+        )
+        .await?;
+
+        let query =
+            "DELETE FROM infraction WHERE infraction_type = 'warn' AND id = $1 AND guild_id = $2";
+        let result = sqlx::query(query)
+            .bind(warn_request.id)
+            .bind(warn_request.guild_id)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(result) if result.rows_affected() == 0 => {
+                return Err(tonic::Status::not_found(format!(
+                    "no warn with id {} in guild {}",
+                    warn_request.id, warn_request.guild_id
+                )))
+            }
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn get_infraction_by_case(
+        &self,
+        request: tonic::Request<proto::CaseRequest>,
+    ) -> Result<tonic::Response<proto::Infraction>, tonic::Status> {
+        info!("handling `get_infraction_by_case`");
+
+        let case_request = request.get_ref();
+
+        auth::authorize(&request, Scope::ModerationRead, Some(case_request.guild_id))?;
+        ensure_guild_active(&self.pool, case_request.guild_id).await?;
+
+        let query = "SELECT * FROM infraction WHERE guild_id = $1 AND case_number = $2";
+        let result = sqlx::query_as::<_, models::moderation::Infraction>(query)
+            .bind(case_request.guild_id)
+            .bind(case_request.case_number)
+            .fetch_one(&self.pool)
+            .await;
+
+        let infraction = match result {
+            Ok(infraction) => infraction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        Ok(tonic::Response::new(proto::Infraction::from(&infraction)))
+    }
+
+    async fn create_infraction(
+        &self,
+        request: tonic::Request<proto::NewInfraction>,
+    ) -> Result<tonic::Response<proto::Infraction>, tonic::Status> {
+        info!("handling `create_infraction`");
+
+        let new_infraction = request.get_ref();
+
+        auth::authorize(
+            &request,
+            Scope::ModerationWrite,
+            Some(new_infraction.guild_id),
+        )?;
+
+        let infraction_type = match proto::InfractionType::try_from(new_infraction.infraction_type)
+        {
+            Ok(infraction_type) => InfractionType::from(infraction_type),
+            Err(_) => {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "unknown infraction type {}",
+                    new_infraction.infraction_type
+                )))
+            }
+        };
+
+        if infraction_type == InfractionType::Warn {
+            return Err(tonic::Status::invalid_argument(
+                "warns must be created with `create_warn` so automod thresholds are applied",
+            ));
+        }
+
+        match new_infraction.duration_seconds {
+            Some(duration_seconds) if duration_seconds <= 0 => {
+                return Err(tonic::Status::invalid_argument(
+                    "duration_seconds must be positive",
+                ))
+            }
+            Some(_) if !infraction_type.is_temporary() => {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "{:?} infractions cannot have a duration",
+                    infraction_type
+                )))
+            }
+            None if infraction_type == InfractionType::Timeout => {
+                return Err(tonic::Status::invalid_argument(
+                    "timeouts require duration_seconds",
+                ))
+            }
+            _ => {}
+        }
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, new_infraction.guild_id).await?;
+
+        permissions::require(
+            &mut *transaction,
+            new_infraction.guild_id,
+            new_infraction.staff.as_ref(),
+            StaffAction::from(infraction_type),
+        )
+        .await?;
+
+        let query = "INSERT INTO infraction (guild_id, infraction_type, staff_member_id, target_user_id, reason, expires_at) \
+            VALUES ($1, $2, $3, $4, $5, NOW() + make_interval(secs => $6)) RETURNING *";
+        let result = sqlx::query_as::<_, models::moderation::Infraction>(query)
+            .bind(new_infraction.guild_id)
+            .bind(infraction_type)
+            .bind(new_infraction.staff_member_id)
+            .bind(new_infraction.target_user_id)
+            .bind(&new_infraction.reason)
+            .bind(
+                new_infraction
+                    .duration_seconds
+                    .map(|seconds| seconds as f64),
+            )
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let infraction = match result {
+            Ok(infraction) => infraction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(proto::Infraction::from(&infraction)))
+    }
+
+    async fn get_infractions(
+        &self,
+        request: tonic::Request<proto::InfractionsRequest>,
+    ) -> Result<tonic::Response<proto::Infractions>, tonic::Status> {
+        info!("handling `get_infractions`");
+
+        let infractions_request = request.get_ref();
+
+        auth::authorize(
+            &request,
+            Scope::ModerationRead,
+            Some(infractions_request.guild_id),
+        )?;
+        ensure_guild_active(&self.pool, infractions_request.guild_id).await?;
+
+        let mut infraction_types = Vec::with_capacity(infractions_request.infraction_types.len());
+        for &infraction_type in &infractions_request.infraction_types {
+            match proto::InfractionType::try_from(infraction_type) {
+                Ok(infraction_type) => infraction_types.push(InfractionType::from(infraction_type)),
+                Err(_) => {
+                    return Err(tonic::Status::invalid_argument(format!(
+                        "unknown infraction type {}",
+                        infraction_type
+                    )))
+                }
+            }
+        }
+
+        let sort_direction =
+            pagination::sort_direction_from_proto(infractions_request.sort_direction)?;
+        let page_size = pagination::page_size(infractions_request.page_size);
+        let page_token = PageToken::decode(&infractions_request.page_token, sort_direction)?;
+
+        let query = format!(
+            "SELECT * FROM infraction WHERE guild_id = $1 AND target_user_id = $2 \
+            AND (cardinality($3::infraction_type[]) = 0 OR infraction_type = ANY($3)) \
+            AND ($4::timestamp IS NULL OR (created_at, id) {} ($4, $5)) \
+            ORDER BY created_at {}, id {} LIMIT $6",
+            sort_direction.comparison(),
+            sort_direction.keyword(),
+            sort_direction.keyword()
+        );
+        let result = sqlx::query_as::<_, models::moderation::Infraction>(&query)
+            .bind(infractions_request.guild_id)
+            .bind(infractions_request.target_user_id)
+            .bind(&infraction_types)
+            .bind(page_token.map(|token| token.created_at))
+            .bind(page_token.map(|token| token.id))
+            .bind(i64::from(page_size) + 1)
+            .fetch_all(&self.pool)
+            .await;
+
+        let mut infractions = match result {
+            Ok(infractions) => infractions,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let next_page_token =
+            pagination::next_page_token(&mut infractions, page_size, sort_direction, |row| {
+                PageToken {
+                    created_at: row.created_at,
+                    id: row.id.into(),
+                }
+            });
+
+        let infractions = infractions.iter().map(proto::Infraction::from).collect();
+
+        Ok(tonic::Response::new(proto::Infractions {
+            infractions,
+            next_page_token,
+        }))
+    }
+
+    async fn watch_expired_infractions(
+        &self,
+        request: tonic::Request<proto::ExpiredInfractionsRequest>,
+    ) -> Result<tonic::Response<Self::WatchExpiredInfractionsStream>, tonic::Status> {
+        info!("handling `watch_expired_infractions`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::ModerationRead, guild_id)?;
+
+        let stream = BroadcastStream::new(self.expired_infractions.subscribe()).filter_map(
+            move |infraction| match infraction {
+                Ok(infraction) if guild_id.is_none() || guild_id == Some(infraction.guild_id) => {
+                    Some(Ok(proto::Infraction::from(&infraction)))
+                }
+                Ok(_) => None,
+                Err(BroadcastStreamRecvError::Lagged(skipped)) => {
+                    warn!(
+                        "expired infraction subscriber lagged, skipped {} infractions",
+                        skipped
+                    );
+                    None
+                }
+            },
+        );
+
+        Ok(tonic::Response::new(Box::pin(stream)))
+    }
+
+    async fn reset_settings(
+        &self,
+        request: tonic::Request<proto::AutomodSettingsRequest>,
+    ) -> Result<tonic::Response<proto::AutomodSettings>, tonic::Status> {
+        info!("handling `reset_settings`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::ModerationWrite, Some(guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, guild_id).await?;
+
+        let query = "INSERT INTO automod_settings (guild_id) VALUES ($1) ON CONFLICT (guild_id) \
+            DO UPDATE SET autoban_enabled = DEFAULT, autoban_threshold = DEFAULT, autokick_enabled = DEFAULT, \
+            autokick_threshold = DEFAULT, warn_expiry_days = DEFAULT RETURNING *";
+        let result = sqlx::query_as::<_, models::moderation::AutomodSettings>(query)
+            .bind(guild_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let settings = match result {
+            Ok(settings) => settings,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(settings.into()))
+    }
 }
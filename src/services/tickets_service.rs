@@ -2,7 +2,16 @@ use proto::tickets_service_server;
 use sqlx::PgPool;
 use tracing::info;
 
-use crate::{models, utils::sqlx_error_to_tonic_status};
+use crate::{
+    auth::{self, Scope},
+    models::{self, staff::StaffAction, tickets::TicketStatus},
+    pagination::{self, OffsetToken, PageToken},
+    permissions,
+    transcript::{self, TranscriptFormat},
+    utils::{
+        ensure_guild_active, lock_active_guild, sqlx_error_to_tonic_status, timestamp_to_datetime,
+    },
+};
 
 pub mod proto {
     tonic::include_proto!("tickets");
@@ -21,6 +30,17 @@ impl From<models::tickets::TicketsSettings> for proto::TicketsSettings {
     }
 }
 
+impl From<models::tickets::TicketStatus> for proto::TicketStatus {
+    fn from(value: models::tickets::TicketStatus) -> Self {
+        match value {
+            models::tickets::TicketStatus::Open => Self::Open,
+            models::tickets::TicketStatus::Claimed => Self::Claimed,
+            models::tickets::TicketStatus::OnHold => Self::OnHold,
+            models::tickets::TicketStatus::Closed => Self::Closed,
+        }
+    }
+}
+
 impl From<models::tickets::Ticket> for proto::Ticket {
     fn from(value: models::tickets::Ticket) -> Self {
         Self {
@@ -30,6 +50,9 @@ impl From<models::tickets::Ticket> for proto::Ticket {
             title: value.title,
             info: value.info,
             created_at: value.created_at.and_utc().timestamp(),
+            status: proto::TicketStatus::from(value.status).into(),
+            claimed_by: value.claimed_by,
+            close_reason: value.close_reason,
         }
     }
 }
@@ -43,6 +66,48 @@ impl From<&models::tickets::Ticket> for proto::Ticket {
             title: value.title.clone(),
             info: value.info.clone(),
             created_at: value.created_at.and_utc().timestamp(),
+            status: proto::TicketStatus::from(value.status).into(),
+            claimed_by: value.claimed_by,
+            close_reason: value.close_reason.clone(),
+        }
+    }
+}
+
+impl From<&models::tickets::TicketHistoryEntry> for proto::TicketHistoryEntry {
+    fn from(value: &models::tickets::TicketHistoryEntry) -> Self {
+        Self {
+            id: value.id,
+            ticket_id: value.ticket_id,
+            actor_id: value.actor_id,
+            from_status: value
+                .from_status
+                .map(|status| proto::TicketStatus::from(status).into()),
+            to_status: proto::TicketStatus::from(value.to_status).into(),
+            reason: value.reason.clone(),
+            created_at: value.created_at.and_utc().timestamp(),
+        }
+    }
+}
+
+impl From<&models::tickets::TicketMessage> for proto::TicketMessage {
+    fn from(value: &models::tickets::TicketMessage) -> Self {
+        Self {
+            id: value.id,
+            ticket_id: value.ticket_id,
+            author_id: value.author_id,
+            content: value.content.clone(),
+            attachment_urls: value.attachment_urls.clone(),
+            created_at: value.created_at.and_utc().timestamp(),
+        }
+    }
+}
+
+impl From<proto::TranscriptFormat> for TranscriptFormat {
+    fn from(value: proto::TranscriptFormat) -> Self {
+        match value {
+            proto::TranscriptFormat::Markdown => Self::Markdown,
+            proto::TranscriptFormat::Html => Self::Html,
+            proto::TranscriptFormat::Json => Self::Json,
         }
     }
 }
@@ -56,6 +121,90 @@ impl TicketsService {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+
+    /// Moves a ticket to `to_status` and records the change in `ticket_history`.
+    async fn transition_ticket(
+        &self,
+        update: &proto::TicketStatusUpdate,
+        to_status: TicketStatus,
+    ) -> Result<models::tickets::Ticket, tonic::Status> {
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, update.guild_id).await?;
+
+        permissions::require(
+            &mut *transaction,
+            update.guild_id,
+            update.staff.as_ref(),
+            StaffAction::ManageTickets,
+        )
+        .await?;
+
+        let query = "SELECT * FROM ticket WHERE id = $1 AND guild_id = $2 FOR UPDATE";
+        let result = sqlx::query_as::<_, models::tickets::Ticket>(query)
+            .bind(update.ticket_id)
+            .bind(update.guild_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let ticket = match result {
+            Ok(ticket) => ticket,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        if !ticket.status.can_transition_to(to_status) {
+            return Err(tonic::Status::failed_precondition(format!(
+                "ticket {} cannot move from {:?} to {:?}",
+                ticket.id, ticket.status, to_status
+            )));
+        }
+
+        let (claimed_by, close_reason) = match to_status {
+            TicketStatus::Open => (None, None),
+            TicketStatus::Claimed => (Some(update.actor_id), None),
+            TicketStatus::OnHold => (ticket.claimed_by, None),
+            TicketStatus::Closed => (ticket.claimed_by, update.reason.clone()),
+        };
+
+        let query = "UPDATE ticket SET status = $2, claimed_by = $3, close_reason = $4 WHERE id = $1 RETURNING *";
+        let result = sqlx::query_as::<_, models::tickets::Ticket>(query)
+            .bind(ticket.id)
+            .bind(to_status)
+            .bind(claimed_by)
+            .bind(close_reason)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let updated_ticket = match result {
+            Ok(ticket) => ticket,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let query = "INSERT INTO ticket_history (ticket_id, actor_id, from_status, to_status, reason) VALUES ($1, $2, $3, $4, $5)";
+        let result = sqlx::query(query)
+            .bind(ticket.id)
+            .bind(update.actor_id)
+            .bind(ticket.status)
+            .bind(to_status)
+            .bind(&update.reason)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(updated_ticket)
+    }
 }
 
 #[tonic::async_trait]
@@ -68,12 +217,21 @@ impl tickets_service_server::TicketsService for TicketsService {
 
         let settings = request.get_ref();
 
+        auth::authorize(&request, Scope::TicketsWrite, Some(settings.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, settings.guild_id).await?;
+
         let query = "INSERT INTO tickets_settings VALUES ($1, $2, $3) ON CONFLICT (guild_id) DO UPDATE SET enabled = $2, channel_id = $3";
         let result = sqlx::query(query)
             .bind(settings.guild_id)
             .bind(settings.enabled)
             .bind(settings.channel_id)
-            .execute(&self.pool)
+            .execute(&mut *transaction)
             .await;
 
         match result {
@@ -81,6 +239,11 @@ impl tickets_service_server::TicketsService for TicketsService {
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
         }
 
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
         Ok(tonic::Response::new(()))
     }
 
@@ -92,6 +255,9 @@ impl tickets_service_server::TicketsService for TicketsService {
 
         let guild_id = request.get_ref().guild_id;
 
+        auth::authorize(&request, Scope::TicketsRead, Some(guild_id))?;
+        ensure_guild_active(&self.pool, guild_id).await?;
+
         let query = "SELECT * FROM tickets_settings WHERE guild_id = $1";
         let result = sqlx::query_as::<_, models::tickets::TicketsSettings>(query)
             .bind(guild_id)
@@ -114,13 +280,36 @@ impl tickets_service_server::TicketsService for TicketsService {
 
         let new_ticket = request.get_ref();
 
-        let query = "INSERT INTO ticket (guild_id, author_id, title, info) VALUES ($1, $2, $3, $4)";
-        let result = sqlx::query(query)
+        auth::authorize(&request, Scope::TicketsWrite, Some(new_ticket.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, new_ticket.guild_id).await?;
+
+        let query = "INSERT INTO ticket (guild_id, author_id, title, info) VALUES ($1, $2, $3, $4) RETURNING id";
+        let result = sqlx::query_scalar::<_, i32>(query)
             .bind(new_ticket.guild_id)
             .bind(new_ticket.author_id)
             .bind(&new_ticket.title)
             .bind(&new_ticket.info)
-            .execute(&self.pool)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let ticket_id = match result {
+            Ok(ticket_id) => ticket_id,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let query =
+            "INSERT INTO ticket_history (ticket_id, actor_id, to_status) VALUES ($1, $2, $3)";
+        let result = sqlx::query(query)
+            .bind(ticket_id)
+            .bind(new_ticket.author_id)
+            .bind(TicketStatus::Open)
+            .execute(&mut *transaction)
             .await;
 
         match result {
@@ -128,6 +317,11 @@ impl tickets_service_server::TicketsService for TicketsService {
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
         }
 
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
         Ok(tonic::Response::new(()))
     }
 
@@ -139,6 +333,9 @@ impl tickets_service_server::TicketsService for TicketsService {
 
         let ticket_request = request.get_ref();
 
+        auth::authorize(&request, Scope::TicketsRead, Some(ticket_request.guild_id))?;
+        ensure_guild_active(&self.pool, ticket_request.guild_id).await?;
+
         let query =
             "SELECT * FROM ticket WHERE guild_id = $1 AND author_id = $2 ORDER BY created_at ASC";
         let result = sqlx::query_as::<_, models::tickets::Ticket>(query)
@@ -163,23 +360,47 @@ impl tickets_service_server::TicketsService for TicketsService {
 
         let ticket_request = request.get_ref();
 
-        let query =
-            "SELECT * FROM ticket WHERE guild_id = $1 AND author_id = $2 ORDER BY created_at ASC LIMIT 5";
-        let result = sqlx::query_as::<_, models::tickets::Ticket>(query)
+        auth::authorize(&request, Scope::TicketsRead, Some(ticket_request.guild_id))?;
+        ensure_guild_active(&self.pool, ticket_request.guild_id).await?;
+
+        let sort_direction = pagination::sort_direction_from_proto(ticket_request.sort_direction)?;
+        let page_size = pagination::page_size(ticket_request.page_size);
+        let page_token = PageToken::decode(&ticket_request.page_token, sort_direction)?;
+
+        let query = format!(
+            "SELECT * FROM ticket WHERE guild_id = $1 AND author_id = $2 \
+            AND ($3::timestamp IS NULL OR (created_at, id) {} ($3, $4)) \
+            ORDER BY created_at {}, id {} LIMIT $5",
+            sort_direction.comparison(),
+            sort_direction.keyword(),
+            sort_direction.keyword()
+        );
+        let result = sqlx::query_as::<_, models::tickets::Ticket>(&query)
             .bind(ticket_request.guild_id)
             .bind(ticket_request.author_id)
+            .bind(page_token.map(|token| token.created_at))
+            .bind(page_token.map(|token| token.id))
+            .bind(i64::from(page_size) + 1)
             .fetch_all(&self.pool)
             .await;
 
-        let tickets = match result {
+        let mut tickets = match result {
             Ok(tickets) => tickets,
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
-        }
-        .iter()
-        .map(|ticket| proto::Ticket::from(ticket))
-        .collect();
+        };
+
+        let next_page_token =
+            pagination::next_page_token(&mut tickets, page_size, sort_direction, |row| PageToken {
+                created_at: row.created_at,
+                id: row.id.into(),
+            });
+
+        let tickets = tickets.iter().map(proto::Ticket::from).collect();
 
-        Ok(tonic::Response::new(proto::Tickets { tickets }))
+        Ok(tonic::Response::new(proto::Tickets {
+            tickets,
+            next_page_token,
+        }))
     }
 
     async fn delete_ticket(
@@ -190,12 +411,29 @@ impl tickets_service_server::TicketsService for TicketsService {
 
         let ticket_request = request.get_ref();
 
+        auth::authorize(&request, Scope::TicketsWrite, Some(ticket_request.guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, ticket_request.guild_id).await?;
+
+        permissions::require(
+            &mut *transaction,
+            ticket_request.guild_id,
+            ticket_request.staff.as_ref(),
+            StaffAction::DeleteTickets,
+        )
+        .await?;
+
         let query =
             "DELETE FROM ticket WHERE guild_id = $1 AND author_id = $2 AND created_at = (SELECT MAX (created_at) FROM ticket WHERE guild_id = $1 AND author_id = $2)";
         let result = sqlx::query(query)
             .bind(ticket_request.guild_id)
             .bind(ticket_request.author_id)
-            .execute(&self.pool)
+            .execute(&mut *transaction)
             .await;
 
         match result {
@@ -203,6 +441,382 @@ impl tickets_service_server::TicketsService for TicketsService {
             Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
         }
 
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
         Ok(tonic::Response::new(()))
     }
+
+    async fn claim_ticket(
+        &self,
+        request: tonic::Request<proto::TicketStatusUpdate>,
+    ) -> Result<tonic::Response<proto::Ticket>, tonic::Status> {
+        info!("handling `claim_ticket`");
+
+        auth::authorize(
+            &request,
+            Scope::TicketsWrite,
+            Some(request.get_ref().guild_id),
+        )?;
+
+        let ticket = self
+            .transition_ticket(request.get_ref(), TicketStatus::Claimed)
+            .await?;
+
+        Ok(tonic::Response::new(ticket.into()))
+    }
+
+    async fn hold_ticket(
+        &self,
+        request: tonic::Request<proto::TicketStatusUpdate>,
+    ) -> Result<tonic::Response<proto::Ticket>, tonic::Status> {
+        info!("handling `hold_ticket`");
+
+        auth::authorize(
+            &request,
+            Scope::TicketsWrite,
+            Some(request.get_ref().guild_id),
+        )?;
+
+        let ticket = self
+            .transition_ticket(request.get_ref(), TicketStatus::OnHold)
+            .await?;
+
+        Ok(tonic::Response::new(ticket.into()))
+    }
+
+    async fn reopen_ticket(
+        &self,
+        request: tonic::Request<proto::TicketStatusUpdate>,
+    ) -> Result<tonic::Response<proto::Ticket>, tonic::Status> {
+        info!("handling `reopen_ticket`");
+
+        auth::authorize(
+            &request,
+            Scope::TicketsWrite,
+            Some(request.get_ref().guild_id),
+        )?;
+
+        let ticket = self
+            .transition_ticket(request.get_ref(), TicketStatus::Open)
+            .await?;
+
+        Ok(tonic::Response::new(ticket.into()))
+    }
+
+    async fn close_ticket(
+        &self,
+        request: tonic::Request<proto::TicketStatusUpdate>,
+    ) -> Result<tonic::Response<proto::Ticket>, tonic::Status> {
+        info!("handling `close_ticket`");
+
+        auth::authorize(
+            &request,
+            Scope::TicketsWrite,
+            Some(request.get_ref().guild_id),
+        )?;
+
+        let ticket = self
+            .transition_ticket(request.get_ref(), TicketStatus::Closed)
+            .await?;
+
+        Ok(tonic::Response::new(ticket.into()))
+    }
+
+    async fn get_ticket_history(
+        &self,
+        request: tonic::Request<proto::TicketHistoryRequest>,
+    ) -> Result<tonic::Response<proto::TicketHistory>, tonic::Status> {
+        info!("handling `get_ticket_history`");
+
+        let history_request = request.get_ref();
+
+        auth::authorize(&request, Scope::TicketsRead, Some(history_request.guild_id))?;
+        ensure_guild_active(&self.pool, history_request.guild_id).await?;
+
+        let query = "SELECT ticket_history.* FROM ticket_history \
+            JOIN ticket ON ticket.id = ticket_history.ticket_id \
+            WHERE ticket_history.ticket_id = $1 AND ticket.guild_id = $2 \
+            ORDER BY ticket_history.created_at ASC, ticket_history.id ASC";
+        let result = sqlx::query_as::<_, models::tickets::TicketHistoryEntry>(query)
+            .bind(history_request.ticket_id)
+            .bind(history_request.guild_id)
+            .fetch_all(&self.pool)
+            .await;
+
+        let entries = match result {
+            Ok(entries) => entries,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+        .iter()
+        .map(|entry| entry.into())
+        .collect();
+
+        Ok(tonic::Response::new(proto::TicketHistory { entries }))
+    }
+
+    async fn create_ticket_message(
+        &self,
+        request: tonic::Request<proto::NewTicketMessage>,
+    ) -> Result<tonic::Response<()>, tonic::Status> {
+        info!("handling `create_ticket_message`");
+
+        let new_message = request.get_ref();
+
+        auth::authorize(&request, Scope::TicketsWrite, Some(new_message.guild_id))?;
+
+        let created_at = match new_message.created_at {
+            Some(timestamp) => Some(timestamp_to_datetime(timestamp)?),
+            None => None,
+        };
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, new_message.guild_id).await?;
+
+        let query = "INSERT INTO ticket_message (ticket_id, author_id, content, attachment_urls, created_at) \
+            SELECT id, $3, $4, $5, COALESCE($6, NOW()) FROM ticket WHERE id = $1 AND guild_id = $2";
+        let result = sqlx::query(query)
+            .bind(new_message.ticket_id)
+            .bind(new_message.guild_id)
+            .bind(new_message.author_id)
+            .bind(&new_message.content)
+            .bind(&new_message.attachment_urls)
+            .bind(created_at)
+            .execute(&mut *transaction)
+            .await;
+
+        match result {
+            Ok(result) if result.rows_affected() == 0 => {
+                return Err(tonic::Status::not_found(format!(
+                    "no ticket with id {} in guild {}",
+                    new_message.ticket_id, new_message.guild_id
+                )))
+            }
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(()))
+    }
+
+    async fn get_ticket_messages(
+        &self,
+        request: tonic::Request<proto::TicketMessagesRequest>,
+    ) -> Result<tonic::Response<proto::TicketMessages>, tonic::Status> {
+        info!("handling `get_ticket_messages`");
+
+        let messages_request = request.get_ref();
+
+        auth::authorize(
+            &request,
+            Scope::TicketsRead,
+            Some(messages_request.guild_id),
+        )?;
+        ensure_guild_active(&self.pool, messages_request.guild_id).await?;
+
+        let query = "SELECT ticket_message.* FROM ticket_message \
+            JOIN ticket ON ticket.id = ticket_message.ticket_id \
+            WHERE ticket_message.ticket_id = $1 AND ticket.guild_id = $2 \
+            ORDER BY ticket_message.created_at ASC, ticket_message.id ASC";
+        let result = sqlx::query_as::<_, models::tickets::TicketMessage>(query)
+            .bind(messages_request.ticket_id)
+            .bind(messages_request.guild_id)
+            .fetch_all(&self.pool)
+            .await;
+
+        let messages = match result {
+            Ok(messages) => messages,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+        .iter()
+        .map(|message| message.into())
+        .collect();
+
+        Ok(tonic::Response::new(proto::TicketMessages { messages }))
+    }
+
+    async fn export_transcript(
+        &self,
+        request: tonic::Request<proto::TranscriptRequest>,
+    ) -> Result<tonic::Response<proto::Transcript>, tonic::Status> {
+        info!("handling `export_transcript`");
+
+        let transcript_request = request.get_ref();
+
+        auth::authorize(
+            &request,
+            Scope::TicketsRead,
+            Some(transcript_request.guild_id),
+        )?;
+        ensure_guild_active(&self.pool, transcript_request.guild_id).await?;
+
+        let format = match proto::TranscriptFormat::try_from(transcript_request.format) {
+            Ok(format) => TranscriptFormat::from(format),
+            Err(_) => {
+                return Err(tonic::Status::invalid_argument(format!(
+                    "unknown transcript format {}",
+                    transcript_request.format
+                )))
+            }
+        };
+
+        let query = "SELECT * FROM ticket WHERE id = $1 AND guild_id = $2";
+        let result = sqlx::query_as::<_, models::tickets::Ticket>(query)
+            .bind(transcript_request.ticket_id)
+            .bind(transcript_request.guild_id)
+            .fetch_one(&self.pool)
+            .await;
+
+        let ticket = match result {
+            Ok(ticket) => ticket,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        if ticket.status != TicketStatus::Closed {
+            return Err(tonic::Status::failed_precondition(format!(
+                "ticket {} must be closed before its transcript is exported",
+                ticket.id
+            )));
+        }
+
+        let query =
+            "SELECT * FROM ticket_message WHERE ticket_id = $1 ORDER BY created_at ASC, id ASC";
+        let result = sqlx::query_as::<_, models::tickets::TicketMessage>(query)
+            .bind(ticket.id)
+            .fetch_all(&self.pool)
+            .await;
+
+        let messages = match result {
+            Ok(messages) => messages,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let content = match transcript::render(&ticket, &messages, format) {
+            Ok(content) => content,
+            Err(error) => return Err(tonic::Status::internal(error.to_string())),
+        };
+
+        Ok(tonic::Response::new(proto::Transcript {
+            filename: transcript::filename(&ticket, format),
+            content,
+        }))
+    }
+
+    async fn search_tickets(
+        &self,
+        request: tonic::Request<proto::TicketSearchRequest>,
+    ) -> Result<tonic::Response<proto::TicketSearchResults>, tonic::Status> {
+        info!("handling `search_tickets`");
+
+        let search_request = request.get_ref();
+
+        auth::authorize(&request, Scope::TicketsRead, search_request.guild_id)?;
+        if let Some(guild_id) = search_request.guild_id {
+            ensure_guild_active(&self.pool, guild_id).await?;
+        }
+
+        if search_request.query.trim().is_empty() {
+            return Err(tonic::Status::invalid_argument("query must not be empty"));
+        }
+
+        let start = match search_request.start {
+            Some(timestamp) => Some(timestamp_to_datetime(timestamp)?),
+            None => None,
+        };
+        let end = match search_request.end {
+            Some(timestamp) => Some(timestamp_to_datetime(timestamp)?),
+            None => None,
+        };
+
+        let page_size = pagination::page_size(search_request.page_size);
+        let page_token = OffsetToken::decode(&search_request.page_token)?;
+
+        let query = "SELECT ticket.*, ts_rank(ticket.search_vector, search_query) AS rank \
+            FROM ticket, websearch_to_tsquery('english', $1) search_query \
+            WHERE ticket.search_vector @@ search_query \
+            AND NOT EXISTS (SELECT 1 FROM guild WHERE guild.guild_id = ticket.guild_id AND guild.deleted_at IS NOT NULL) \
+            AND ($2::bigint IS NULL OR ticket.guild_id = $2) \
+            AND ($3::bigint IS NULL OR ticket.author_id = $3) \
+            AND ($4::timestamp IS NULL OR ticket.created_at >= $4) \
+            AND ($5::timestamp IS NULL OR ticket.created_at < $5) \
+            ORDER BY rank DESC, ticket.created_at DESC, ticket.id DESC LIMIT $6 OFFSET $7";
+        let result = sqlx::query_as::<_, models::tickets::TicketSearchHit>(query)
+            .bind(&search_request.query)
+            .bind(search_request.guild_id)
+            .bind(search_request.author_id)
+            .bind(start)
+            .bind(end)
+            .bind(i64::from(page_size) + 1)
+            .bind(page_token.offset)
+            .fetch_all(&self.pool)
+            .await;
+
+        let mut hits = match result {
+            Ok(hits) => hits,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        let next_page_token = page_token.next_page_token(&mut hits, page_size);
+
+        let hits = hits
+            .iter()
+            .map(|hit| proto::TicketSearchHit {
+                ticket: Some(proto::Ticket::from(&hit.ticket)),
+                rank: hit.rank,
+            })
+            .collect();
+
+        Ok(tonic::Response::new(proto::TicketSearchResults {
+            hits,
+            next_page_token,
+        }))
+    }
+
+    async fn reset_settings(
+        &self,
+        request: tonic::Request<proto::TicketsSettingsRequest>,
+    ) -> Result<tonic::Response<proto::TicketsSettings>, tonic::Status> {
+        info!("handling `reset_settings`");
+
+        let guild_id = request.get_ref().guild_id;
+
+        auth::authorize(&request, Scope::TicketsWrite, Some(guild_id))?;
+
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        lock_active_guild(&mut *transaction, guild_id).await?;
+
+        let query = "INSERT INTO tickets_settings (guild_id) VALUES ($1) ON CONFLICT (guild_id) \
+            DO UPDATE SET enabled = DEFAULT, channel_id = DEFAULT RETURNING *";
+        let result = sqlx::query_as::<_, models::tickets::TicketsSettings>(query)
+            .bind(guild_id)
+            .fetch_one(&mut *transaction)
+            .await;
+
+        let settings = match result {
+            Ok(settings) => settings,
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        };
+
+        match transaction.commit().await {
+            Ok(_) => {}
+            Err(error) => return Err(sqlx_error_to_tonic_status(&error)),
+        }
+
+        Ok(tonic::Response::new(settings.into()))
+    }
 }
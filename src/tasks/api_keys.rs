@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::auth::ApiKeyCache;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Reloads the API key cache so keys created or revoked through other replicas are picked up.
+pub async fn run(pool: PgPool, api_keys: ApiKeyCache) {
+    info!("starting API key refresh task");
+
+    let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = api_keys.refresh(&pool).await {
+            error!("failed to refresh API keys: {}", error);
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::{
+    events::{self, Event, EventKind, Operation},
+    models,
+};
+
+const MAX_POLL_INTERVAL: Duration = Duration::from_secs(30);
+const BATCH_SIZE: i64 = 100;
+
+/// Lifts timed infractions once they expire. Pending expiries live in Postgres, so anything that
+/// expired while the server was down is lifted on the first pass after boot. Infractions in
+/// deleted guilds wait until the guild is restored, since the bot is no longer there to lift them.
+pub async fn run(pool: PgPool) {
+    info!("starting infraction expiry task");
+
+    loop {
+        match lift_expired(&pool).await {
+            Ok(0) => {}
+            Ok(lifted) => info!("lifted {} expired infractions", lifted),
+            Err(error) => error!("failed to lift expired infractions: {}", error),
+        }
+
+        let delay = match next_expiry(&pool).await {
+            Ok(Some(delay)) => delay.min(MAX_POLL_INTERVAL),
+            Ok(None) => MAX_POLL_INTERVAL,
+            Err(error) => {
+                error!("failed to find next infraction expiry: {}", error);
+                MAX_POLL_INTERVAL
+            }
+        };
+
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn lift_expired(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let mut lifted = 0;
+
+    loop {
+        // Lifting a batch and announcing it commit together, so a failed notification leaves the
+        // batch to be lifted again instead of losing its events.
+        let mut transaction = pool.begin().await?;
+
+        // Each expiry is announced as `InfractionExpired` below, rather than also as the generic
+        // infraction update the table trigger would send.
+        sqlx::query("SET LOCAL server_events.suppress_triggers = 'on'")
+            .execute(&mut *transaction)
+            .await?;
+
+        // SKIP LOCKED lets several replicas run this task without lifting the same row twice.
+        let query = "UPDATE infraction SET lifted_at = LOCALTIMESTAMP WHERE id IN ( \
+            SELECT infraction.id FROM infraction JOIN guild USING (guild_id) \
+            WHERE infraction.lifted_at IS NULL AND infraction.expires_at <= LOCALTIMESTAMP \
+            AND guild.deleted_at IS NULL \
+            ORDER BY infraction.expires_at LIMIT $1 FOR UPDATE OF infraction SKIP LOCKED) RETURNING *";
+        let infractions = sqlx::query_as::<_, models::moderation::Infraction>(query)
+            .bind(BATCH_SIZE)
+            .fetch_all(&mut *transaction)
+            .await?;
+
+        let batch_size = infractions.len();
+
+        for infraction in infractions {
+            let event = Event {
+                kind: EventKind::InfractionExpired,
+                operation: Operation::Update,
+                guild_id: infraction.guild_id,
+                entity_id: Some(infraction.id.into()),
+            };
+
+            events::notify(&mut *transaction, &event).await?;
+        }
+
+        transaction.commit().await?;
+        lifted += batch_size;
+
+        if batch_size < BATCH_SIZE as usize {
+            return Ok(lifted);
+        }
+    }
+}
+
+async fn next_expiry(pool: &PgPool) -> Result<Option<Duration>, sqlx::Error> {
+    let query = "SELECT EXTRACT(EPOCH FROM MIN(infraction.expires_at) - LOCALTIMESTAMP)::float8 \
+        FROM infraction JOIN guild USING (guild_id) \
+        WHERE infraction.lifted_at IS NULL AND infraction.expires_at IS NOT NULL \
+        AND guild.deleted_at IS NULL";
+    let seconds = sqlx::query_scalar::<_, Option<f64>>(query)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(seconds.map(|seconds| Duration::from_secs_f64(seconds.max(0.0))))
+}
@@ -0,0 +1,39 @@
+use std::time::Duration;
+
+use sqlx::{postgres::PgListener, PgPool};
+use tracing::{error, info, warn};
+
+use crate::events::{self, Event, EventBus};
+
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Forwards events published on the Postgres channel to this replica's subscribers.
+pub async fn run(pool: PgPool, events: EventBus) {
+    loop {
+        if let Err(error) = listen(&pool, &events).await {
+            error!("event listener failed: {}", error);
+        }
+
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+async fn listen(pool: &PgPool, events: &EventBus) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(events::CHANNEL).await?;
+
+    info!("listening for events on `{}`", events::CHANNEL);
+
+    loop {
+        let notification = listener.recv().await?;
+
+        match serde_json::from_str::<Event>(notification.payload()) {
+            Ok(event) => events.publish(event),
+            Err(error) => warn!(
+                "ignoring malformed event `{}`: {}",
+                notification.payload(),
+                error
+            ),
+        }
+    }
+}
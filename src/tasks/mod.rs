@@ -0,0 +1,5 @@
+pub mod api_keys;
+pub mod expiry;
+pub mod listener;
+pub mod prune;
+pub mod purge;
@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const BATCH_SIZE: i64 = 1000;
+const BATCH_PAUSE: Duration = Duration::from_millis(100);
+
+/// Deletes audit events older than their guild's retention period. Rows are removed in small
+/// batches with a pause in between so the table is never locked for long. Deleted guilds are left
+/// alone so a restored guild gets its data back as it was.
+pub async fn run(pool: PgPool) {
+    info!("starting audit event pruning task");
+
+    let mut interval = tokio::time::interval(PRUNE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match prune(&pool).await {
+            Ok(pruned) => info!("pruned {} expired audit events", pruned),
+            Err(error) => error!("failed to prune audit events: {}", error),
+        }
+    }
+}
+
+async fn prune(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let query = "INSERT INTO audit_prune_run DEFAULT VALUES RETURNING id";
+    let run_id = sqlx::query_scalar::<_, i32>(query).fetch_one(pool).await?;
+
+    let mut pruned = 0;
+
+    loop {
+        let query = "DELETE FROM audit_event WHERE id IN ( \
+            SELECT audit_event.id FROM audit_event JOIN logs_settings USING (guild_id) JOIN guild USING (guild_id) \
+            WHERE logs_settings.retention_days > 0 AND guild.deleted_at IS NULL \
+            AND audit_event.created_at < LOCALTIMESTAMP - make_interval(days => logs_settings.retention_days) \
+            LIMIT $1 FOR UPDATE OF audit_event SKIP LOCKED)";
+        let result = sqlx::query(query).bind(BATCH_SIZE).execute(pool).await?;
+
+        pruned += result.rows_affected();
+
+        if result.rows_affected() < BATCH_SIZE as u64 {
+            break;
+        }
+
+        tokio::time::sleep(BATCH_PAUSE).await;
+    }
+
+    let query = "UPDATE audit_prune_run SET finished_at = NOW(), rows_pruned = $2 WHERE id = $1";
+    sqlx::query(query)
+        .bind(run_id)
+        .bind(pruned as i64)
+        .execute(pool)
+        .await?;
+
+    Ok(pruned)
+}
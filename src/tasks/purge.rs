@@ -0,0 +1,59 @@
+use std::time::Duration;
+
+use sqlx::PgPool;
+use tracing::{error, info};
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Permanently deletes guilds that have been soft-deleted for longer than `grace_days`, along with
+/// everything that cascades from them.
+pub async fn run(pool: PgPool, grace_days: i32) {
+    info!(
+        "starting guild purge task with a {} day grace period",
+        grace_days
+    );
+
+    let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        match purge(&pool, grace_days).await {
+            Ok(0) => {}
+            Ok(purged) => info!("purged {} deleted guilds", purged),
+            Err(error) => error!("failed to purge deleted guilds: {}", error),
+        }
+    }
+}
+
+async fn purge(pool: &PgPool, grace_days: i32) -> Result<u64, sqlx::Error> {
+    let mut purged = 0;
+
+    // One guild per transaction keeps each cascade, and the locks it takes, reasonably small.
+    loop {
+        let mut transaction = pool.begin().await?;
+
+        // A purged guild has no one left to tell, so the cascade should not send a change event
+        // for every row it removes.
+        sqlx::query("SET LOCAL server_events.suppress_triggers = 'on'")
+            .execute(&mut *transaction)
+            .await?;
+
+        let query = "DELETE FROM guild WHERE guild_id = ( \
+            SELECT guild_id FROM guild \
+            WHERE deleted_at < LOCALTIMESTAMP - make_interval(days => $1) \
+            LIMIT 1 FOR UPDATE SKIP LOCKED)";
+        let result = sqlx::query(query)
+            .bind(grace_days)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(purged);
+        }
+
+        purged += result.rows_affected();
+    }
+}
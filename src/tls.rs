@@ -0,0 +1,129 @@
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+use tracing::info;
+
+/// Builds the server's TLS configuration from the PEM files at `TLS_CERT_PATH` and `TLS_KEY_PATH`,
+/// or returns `None` to serve plaintext when neither is set. Also setting `TLS_CLIENT_CA_PATH`
+/// enables mutual TLS, where clients must present a certificate signed by that CA.
+pub fn config_from_env() -> Result<Option<ServerTlsConfig>, Box<dyn std::error::Error>> {
+    config_from(
+        std::env::var("TLS_CERT_PATH").ok(),
+        std::env::var("TLS_KEY_PATH").ok(),
+        std::env::var("TLS_CLIENT_CA_PATH").ok(),
+    )
+}
+
+fn config_from(
+    cert_path: Option<String>,
+    key_path: Option<String>,
+    client_ca_path: Option<String>,
+) -> Result<Option<ServerTlsConfig>, Box<dyn std::error::Error>> {
+    let (cert_path, key_path) = match (cert_path, key_path) {
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (None, None) if client_ca_path.is_none() => return Ok(None),
+        (None, None) => {
+            return Err("TLS_CLIENT_CA_PATH requires TLS_CERT_PATH and TLS_KEY_PATH".into())
+        }
+        _ => return Err("TLS_CERT_PATH and TLS_KEY_PATH must be set together".into()),
+    };
+
+    let cert = read_pem(&cert_path)?;
+    let key = read_pem(&key_path)?;
+
+    let mut config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+
+    match client_ca_path {
+        Some(client_ca_path) => {
+            let client_ca = read_pem(&client_ca_path)?;
+            config = config.client_ca_root(Certificate::from_pem(client_ca));
+
+            info!("serving with mutual TLS, client certificates are required");
+        }
+        None => info!("serving with TLS"),
+    }
+
+    Ok(Some(config))
+}
+
+fn read_pem(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|error| format!("failed to read {}: {}", path, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(value: &str) -> Option<String> {
+        Some(String::from(value))
+    }
+
+    fn error(
+        cert_path: Option<String>,
+        key_path: Option<String>,
+        client_ca_path: Option<String>,
+    ) -> String {
+        config_from(cert_path, key_path, client_ca_path)
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn serves_plaintext_without_any_paths() {
+        assert!(config_from(None, None, None).unwrap().is_none());
+    }
+
+    #[test]
+    fn requires_the_key_with_the_cert() {
+        assert_eq!(
+            error(path("cert.pem"), None, None),
+            "TLS_CERT_PATH and TLS_KEY_PATH must be set together"
+        );
+    }
+
+    #[test]
+    fn requires_the_cert_with_the_key() {
+        assert_eq!(
+            error(None, path("key.pem"), path("ca.pem")),
+            "TLS_CERT_PATH and TLS_KEY_PATH must be set together"
+        );
+    }
+
+    #[test]
+    fn requires_the_cert_and_key_with_a_client_ca() {
+        assert_eq!(
+            error(None, None, path("ca.pem")),
+            "TLS_CLIENT_CA_PATH requires TLS_CERT_PATH and TLS_KEY_PATH"
+        );
+    }
+
+    #[test]
+    fn reports_an_unreadable_file() {
+        let message = error(path("/nonexistent/cert.pem"), path("key.pem"), None);
+
+        assert!(message.starts_with("failed to read /nonexistent/cert.pem: "));
+    }
+
+    // The PEM contents are only parsed once the server starts, so this checks that every file is
+    // read rather than that the certificates are valid.
+    #[test]
+    fn reads_every_file_without_parsing_it() {
+        let dir =
+            std::env::temp_dir().join(format!("tls-test-reads-every-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut paths = Vec::new();
+        for name in ["cert.pem", "key.pem", "ca.pem"] {
+            let path = dir.join(name);
+            std::fs::write(&path, "placeholder").unwrap();
+            paths.push(path.to_string_lossy().into_owned());
+        }
+
+        let config = config_from(
+            Some(paths[0].clone()),
+            Some(paths[1].clone()),
+            Some(paths[2].clone()),
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(config.unwrap().is_some());
+    }
+}
@@ -0,0 +1,376 @@
+use std::fmt::{self, Write};
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+use crate::models::tickets::{Ticket, TicketMessage};
+
+#[derive(Debug, Clone, Copy)]
+pub enum TranscriptFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl TranscriptFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            TranscriptFormat::Markdown => "md",
+            TranscriptFormat::Html => "html",
+            TranscriptFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonTranscript<'a> {
+    ticket: &'a Ticket,
+    messages: &'a [TicketMessage],
+}
+
+pub fn filename(ticket: &Ticket, format: TranscriptFormat) -> String {
+    format!(
+        "ticket-{}-{}.{}",
+        ticket.guild_id,
+        ticket.id,
+        format.extension()
+    )
+}
+
+pub fn render(
+    ticket: &Ticket,
+    messages: &[TicketMessage],
+    format: TranscriptFormat,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let content = match format {
+        TranscriptFormat::Markdown => render_markdown(ticket, messages)?.into_bytes(),
+        TranscriptFormat::Html => render_html(ticket, messages)?.into_bytes(),
+        TranscriptFormat::Json => serde_json::to_vec_pretty(&JsonTranscript { ticket, messages })?,
+    };
+
+    Ok(content)
+}
+
+fn format_timestamp(timestamp: &NaiveDateTime) -> String {
+    timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+/// Escapes `value` so Markdown renders it as literal text, one source line per output line.
+fn escape_markdown(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for (index, line) in value.lines().enumerate() {
+        if index > 0 {
+            escaped.push('\n');
+        }
+
+        let text = line.trim_start();
+        escaped.push_str(&line[..line.len() - text.len()]);
+
+        // Headings, list items and setext underlines are only recognised at the start of a line.
+        let digits = text.bytes().take_while(u8::is_ascii_digit).count();
+        let text = if digits > 0 && matches!(text.as_bytes().get(digits), Some(b'.' | b')')) {
+            escaped.push_str(&text[..digits]);
+            escaped.push('\\');
+            &text[digits..]
+        } else {
+            if text.starts_with(['#', '-', '+', '=']) {
+                escaped.push('\\');
+            }
+            text
+        };
+
+        for character in text.chars() {
+            if matches!(
+                character,
+                '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~' | '&'
+            ) {
+                escaped.push('\\');
+            }
+            escaped.push(character);
+        }
+    }
+
+    escaped
+}
+
+/// Percent-encodes the characters an autolink may not contain, so a URL can't close its `<...>`
+/// early or spill onto another line.
+fn encode_autolink(url: &str) -> String {
+    let mut encoded = String::with_capacity(url.len());
+    for character in url.chars() {
+        if character == '<'
+            || character == '>'
+            || character.is_whitespace()
+            || character.is_control()
+        {
+            let mut bytes = [0; 4];
+            for byte in character.encode_utf8(&mut bytes).bytes() {
+                write!(encoded, "%{:02X}", byte).unwrap();
+            }
+        } else {
+            encoded.push(character);
+        }
+    }
+    encoded
+}
+
+fn render_markdown(ticket: &Ticket, messages: &[TicketMessage]) -> Result<String, fmt::Error> {
+    let mut output = String::new();
+
+    writeln!(
+        output,
+        "# Ticket #{}: {}\n",
+        ticket.id,
+        escape_markdown(&ticket.title.replace(['\r', '\n'], " "))
+    )?;
+    writeln!(output, "- **Guild:** {}", ticket.guild_id)?;
+    writeln!(output, "- **Author:** {}", ticket.author_id)?;
+    writeln!(output, "- **Status:** {:?}", ticket.status)?;
+    writeln!(
+        output,
+        "- **Opened:** {}",
+        format_timestamp(&ticket.created_at)
+    )?;
+    if let Some(claimed_by) = ticket.claimed_by {
+        writeln!(output, "- **Claimed by:** {}", claimed_by)?;
+    }
+    if let Some(close_reason) = &ticket.close_reason {
+        writeln!(
+            output,
+            "- **Close reason:** {}",
+            escape_markdown(&close_reason.replace(['\r', '\n'], " "))
+        )?;
+    }
+
+    writeln!(output)?;
+    for line in ticket.info.lines() {
+        writeln!(output, "> {}", escape_markdown(line))?;
+    }
+
+    writeln!(output, "\n## Messages")?;
+    for message in messages {
+        writeln!(
+            output,
+            "\n**{}** ({})\n",
+            message.author_id,
+            format_timestamp(&message.created_at)
+        )?;
+        writeln!(output, "{}", escape_markdown(&message.content))?;
+        // As in the HTML transcript, only http(s) URLs become links.
+        for attachment_url in &message.attachment_urls {
+            if attachment_url.starts_with("https://") || attachment_url.starts_with("http://") {
+                writeln!(output, "- <{}>", encode_autolink(attachment_url))?;
+            } else {
+                writeln!(
+                    output,
+                    "- {}",
+                    escape_markdown(&attachment_url.replace(['\r', '\n'], " "))
+                )?;
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for character in value.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str =
+    "body{font-family:sans-serif;background:#313338;color:#dbdee1;margin:2rem}\
+    header{border-bottom:1px solid #4e5058;margin-bottom:1rem}\
+    .info{white-space:pre-wrap;border-left:4px solid #4e5058;padding-left:.75rem}\
+    .message{margin:1rem 0}\
+    .author{font-weight:bold;color:#f2f3f5}\
+    .timestamp{color:#949ba4;font-size:.8rem;margin-left:.5rem}\
+    .content{white-space:pre-wrap}\
+    a{color:#00a8fc}";
+
+fn render_html(ticket: &Ticket, messages: &[TicketMessage]) -> Result<String, fmt::Error> {
+    let mut output = String::new();
+
+    write!(
+        output,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>Ticket #{} - {}</title><style>{}</style></head><body>",
+        ticket.id,
+        escape_html(&ticket.title),
+        HTML_STYLE
+    )?;
+
+    write!(
+        output,
+        "<header><h1>Ticket #{}: {}</h1><ul><li>Guild: {}</li><li>Author: {}</li><li>Status: {:?}</li><li>Opened: {}</li>",
+        ticket.id,
+        escape_html(&ticket.title),
+        ticket.guild_id,
+        ticket.author_id,
+        ticket.status,
+        format_timestamp(&ticket.created_at)
+    )?;
+    if let Some(claimed_by) = ticket.claimed_by {
+        write!(output, "<li>Claimed by: {}</li>", claimed_by)?;
+    }
+    if let Some(close_reason) = &ticket.close_reason {
+        write!(
+            output,
+            "<li>Close reason: {}</li>",
+            escape_html(close_reason)
+        )?;
+    }
+    write!(
+        output,
+        "</ul><p class=\"info\">{}</p></header><main>",
+        escape_html(&ticket.info)
+    )?;
+
+    for message in messages {
+        write!(
+            output,
+            "<div class=\"message\"><span class=\"author\">{}</span><span class=\"timestamp\">{}</span><div class=\"content\">{}</div>",
+            message.author_id,
+            format_timestamp(&message.created_at),
+            escape_html(&message.content)
+        )?;
+        for attachment_url in &message.attachment_urls {
+            let escaped_url = escape_html(attachment_url);
+            if attachment_url.starts_with("https://") || attachment_url.starts_with("http://") {
+                write!(
+                    output,
+                    "<div class=\"attachment\"><a href=\"{}\">{}</a></div>",
+                    escaped_url, escaped_url
+                )?;
+            } else {
+                write!(output, "<div class=\"attachment\">{}</div>", escaped_url)?;
+            }
+        }
+        output.push_str("</div>");
+    }
+
+    output.push_str("</main></body></html>\n");
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::tickets::TicketStatus;
+
+    fn ticket(title: &str) -> Ticket {
+        Ticket {
+            id: 7,
+            guild_id: 1,
+            author_id: 2,
+            title: String::from(title),
+            info: String::from("Some info"),
+            created_at: NaiveDateTime::default(),
+            status: TicketStatus::Closed,
+            claimed_by: None,
+            close_reason: None,
+        }
+    }
+
+    fn message(content: &str) -> TicketMessage {
+        TicketMessage {
+            id: 1,
+            ticket_id: 7,
+            author_id: 3,
+            content: String::from(content),
+            attachment_urls: Vec::new(),
+            created_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn escape_markdown_escapes_inline_syntax() {
+        assert_eq!(
+            escape_markdown("`code` *bold* [link](url) <b> a|b ~x~ &amp; \\"),
+            "\\`code\\` \\*bold\\* \\[link\\](url) \\<b\\> a\\|b \\~x\\~ \\&amp; \\\\"
+        );
+    }
+
+    #[test]
+    fn escape_markdown_escapes_block_markers_at_line_start() {
+        assert_eq!(
+            escape_markdown("# heading\n- item\n+ item\n12. item\n3) item\n  > quote\n==="),
+            "\\# heading\n\\- item\n\\+ item\n12\\. item\n3\\) item\n  \\> quote\n\\==="
+        );
+    }
+
+    #[test]
+    fn escape_markdown_leaves_plain_text_alone() {
+        assert_eq!(
+            escape_markdown("issue #1 - version 2.5 (again)"),
+            "issue #1 - version 2.5 (again)"
+        );
+    }
+
+    #[test]
+    fn markdown_title_stays_in_the_heading() {
+        let output = render_markdown(&ticket("# x `y`\nmore"), &[]).unwrap();
+
+        assert_eq!(
+            output.lines().next(),
+            Some("# Ticket #7: \\# x \\`y\\` more")
+        );
+    }
+
+    #[test]
+    fn markdown_escapes_message_content() {
+        let output = render_markdown(&ticket("Help"), &[message("```\n# not a heading")]).unwrap();
+
+        assert!(output.contains("\\`\\`\\`\n\\# not a heading\n"));
+        assert!(!output.contains("\n```"));
+    }
+
+    #[test]
+    fn markdown_attachment_urls_stay_in_their_link() {
+        let mut message = message("see attached");
+        message.attachment_urls = vec![
+            String::from("https://cdn.example.com/a b.png><script>\n# x"),
+            String::from("javascript:alert(1)"),
+        ];
+
+        let output = render_markdown(&ticket("Help"), &[message]).unwrap();
+
+        assert!(output.contains("- <https://cdn.example.com/a%20b.png%3E%3Cscript%3E%0A#%20x>\n"));
+        assert!(output.contains("- javascript:alert(1)\n"));
+        assert!(!output.contains("<script>"));
+        assert!(!output.contains("<javascript:"));
+    }
+
+    #[test]
+    fn html_escapes_title_and_content() {
+        let output = render_html(
+            &ticket("<script>alert(1)</script>"),
+            &[message("a & \"b\"")],
+        )
+        .unwrap();
+
+        assert!(output.contains("&lt;script&gt;alert(1)&lt;/script&gt;"));
+        assert!(output.contains("a &amp; &quot;b&quot;"));
+        assert!(!output.contains("<script>"));
+    }
+
+    #[test]
+    fn json_includes_ticket_and_messages() {
+        let output = render(&ticket("Help"), &[message("hello")], TranscriptFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(value["ticket"]["title"], "Help");
+        assert_eq!(value["messages"][0]["content"], "hello");
+    }
+}
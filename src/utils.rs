@@ -1,4 +1,5 @@
-use sqlx::Error;
+use chrono::{DateTime, NaiveDateTime};
+use sqlx::{Error, PgExecutor};
 use tonic::Status;
 
 pub fn sqlx_error_to_tonic_status(error: &Error) -> Status {
@@ -9,3 +10,58 @@ pub fn sqlx_error_to_tonic_status(error: &Error) -> Status {
         _ => Status::internal(error.to_string()),
     }
 }
+
+pub fn timestamp_to_datetime(timestamp: i64) -> Result<NaiveDateTime, Status> {
+    match DateTime::from_timestamp(timestamp, 0) {
+        Some(datetime) => Ok(datetime.naive_utc()),
+        None => Err(Status::invalid_argument(format!(
+            "timestamp {} is out of range",
+            timestamp
+        ))),
+    }
+}
+
+/// Rejects requests for a guild that has been deleted and is awaiting purge, so its data is
+/// neither read nor changed unless the guild is restored.
+pub async fn ensure_guild_active<'e>(
+    executor: impl PgExecutor<'e>,
+    guild_id: i64,
+) -> Result<(), Status> {
+    let query =
+        "SELECT EXISTS (SELECT 1 FROM guild WHERE guild_id = $1 AND deleted_at IS NOT NULL)";
+    let result = sqlx::query_scalar::<_, bool>(query)
+        .bind(guild_id)
+        .fetch_one(executor)
+        .await;
+
+    match result {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(Status::not_found(format!(
+            "guild {} has been deleted",
+            guild_id
+        ))),
+        Err(error) => Err(sqlx_error_to_tonic_status(&error)),
+    }
+}
+
+/// Like [`ensure_guild_active`], but share-locks the guild row for the rest of the transaction, so
+/// the guild cannot be deleted between this check and the writes that follow it.
+pub async fn lock_active_guild<'e>(
+    executor: impl PgExecutor<'e>,
+    guild_id: i64,
+) -> Result<(), Status> {
+    let query = "SELECT deleted_at IS NOT NULL FROM guild WHERE guild_id = $1 FOR SHARE";
+    let result = sqlx::query_scalar::<_, bool>(query)
+        .bind(guild_id)
+        .fetch_optional(executor)
+        .await;
+
+    match result {
+        Ok(None) | Ok(Some(false)) => Ok(()),
+        Ok(Some(true)) => Err(Status::not_found(format!(
+            "guild {} has been deleted",
+            guild_id
+        ))),
+        Err(error) => Err(sqlx_error_to_tonic_status(&error)),
+    }
+}